@@ -0,0 +1,120 @@
+//! Generates the `Layer` enum from the template tree, and checks that every layer of
+//! `src/template/manifest.json` exists, so a typo fails the build instead of the scaffolding.
+
+use std::{
+  env, fs,
+  path::{Path, PathBuf},
+};
+
+const TEMPLATE_DIR: &str = "src/template";
+
+fn main() {
+  println!("cargo:rerun-if-changed={}", TEMPLATE_DIR);
+
+  let layers = find_layers(Path::new(TEMPLATE_DIR));
+  check_manifest(&layers);
+
+  let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+  fs::write(out_dir.join("layers.rs"), generate(&layers)).unwrap();
+}
+
+/// A top-level directory with files of its own is a layer, e.g. `base`. One holding only
+/// directories groups layers, e.g. `config/jsx`.
+fn find_layers(template_dir: &Path) -> Vec<String> {
+  let mut layers = vec![];
+  for group in sorted_entries(template_dir) {
+    if !group.is_dir() {
+      continue;
+    }
+    let group_name = file_name(&group);
+    let children = sorted_entries(&group);
+    if children.iter().all(|child| child.is_dir()) {
+      for child in children {
+        layers.push(format!("{}/{}", group_name, file_name(&child)));
+      }
+    } else {
+      layers.push(group_name);
+    }
+  }
+  layers
+}
+
+fn check_manifest(layers: &[String]) {
+  let path = Path::new(TEMPLATE_DIR).join("manifest.json");
+  let manifest: serde_json::Value =
+    serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+  for layer in manifest["layers"].as_array().expect("manifest.json has no layers") {
+    let layer_path = layer["path"].as_str().expect("manifest.json has a layer without a path");
+    if !layers.iter().any(|layer| layer == layer_path) {
+      panic!(
+        "manifest.json refers to layer {:?}, which is not in {}, known layers: {}",
+        layer_path,
+        TEMPLATE_DIR,
+        layers.join(", ")
+      );
+    }
+  }
+}
+
+fn generate(layers: &[String]) -> String {
+  let variants = layers.iter().map(|layer| variant_name(layer)).collect::<Vec<_>>();
+
+  let mut code = String::new();
+  code.push_str("/// A layer of the embedded template tree, generated by `build.rs`.\n");
+  code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+  code.push_str("pub enum Layer {\n");
+  for (layer, variant) in layers.iter().zip(&variants) {
+    code.push_str(&format!("  /// `{}`\n  {},\n", layer, variant));
+  }
+  code.push_str("}\n\nimpl Layer {\n");
+
+  code.push_str("  pub const ALL: &'static [Layer] = &[\n");
+  for variant in &variants {
+    code.push_str(&format!("    Layer::{},\n", variant));
+  }
+  code.push_str("  ];\n\n");
+
+  code.push_str("  /// The layer directory, relative to the template root.\n");
+  code.push_str("  pub fn path(self) -> &'static str {\n    match self {\n");
+  for (layer, variant) in layers.iter().zip(&variants) {
+    code.push_str(&format!("      Layer::{} => {:?},\n", variant, layer));
+  }
+  code.push_str("    }\n  }\n\n");
+
+  code.push_str("  pub fn from_path(path: &str) -> Option<Layer> {\n    match path {\n");
+  for (layer, variant) in layers.iter().zip(&variants) {
+    code.push_str(&format!("      {:?} => Some(Layer::{}),\n", layer, variant));
+  }
+  code.push_str("      _ => None,\n    }\n  }\n}\n");
+
+  code
+}
+
+/// `config/cypress-ct` becomes `ConfigCypressCt`.
+fn variant_name(layer: &str) -> String {
+  layer
+    .split(|char: char| !char.is_ascii_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(|word| {
+      let mut chars = word.chars();
+      match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+      }
+    })
+    .collect()
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+  let mut entries = fs::read_dir(dir)
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .collect::<Vec<_>>();
+  entries.sort();
+  entries
+}
+
+fn file_name(path: &Path) -> String {
+  path.file_name().unwrap().to_string_lossy().into_owned()
+}
@@ -0,0 +1,371 @@
+use std::{
+  fmt::Display,
+  io,
+  path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::{
+  convert_typescript::{convert_to_typescript, remove_typescript_files},
+  error::{Error, Result},
+  manifest::Manifest,
+  render::render_template,
+  render_eslint::RenderEslint,
+  scaffold::{E2eTesting, ScaffoldOptions},
+  templates::{get_template, Layer},
+  vfs::{FileSystem, MemoryFs},
+};
+
+/// A feature that can be added to an existing project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Feature {
+  Jsx,
+  Router,
+  Pinia,
+  Vitest,
+  Cypress,
+  Playwright,
+  Eslint,
+}
+
+impl Display for Feature {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Feature::Jsx => write!(f, "JSX"),
+      Feature::Router => write!(f, "Vue Router"),
+      Feature::Pinia => write!(f, "Pinia"),
+      Feature::Vitest => write!(f, "Vitest"),
+      Feature::Cypress => write!(f, "Cypress"),
+      Feature::Playwright => write!(f, "Playwright"),
+      Feature::Eslint => write!(f, "ESLint"),
+    }
+  }
+}
+
+/// The files [`add_feature`] or [`generate`](crate::generate) changed in a project.
+#[derive(Debug, Default)]
+pub struct ChangeReport {
+  pub created: Vec<PathBuf>,
+  /// Existing files that were merged or patched, like `package.json` and the entry file.
+  pub updated: Vec<PathBuf>,
+  /// Existing files that would have been overwritten, left untouched.
+  pub conflicts: Vec<PathBuf>,
+}
+
+/// Files the templates merge into rather than overwrite.
+const MERGED_FILES: [&str; 2] = ["package.json", ".gitignore"];
+
+/// Adds `feature` to the project in `root`.
+///
+/// Only the layers the feature adds to the manifest are rendered, `package.json` and
+/// `.gitignore` are merged, and the entry file is patched to install the plugin. Files that
+/// already exist with different contents are reported as conflicts instead of overwritten.
+pub fn add_feature(root: &Path, feature: Feature, fs: &mut dyn FileSystem) -> Result<ChangeReport> {
+  let current = detect_options(root, fs)?;
+  if has_feature(&current, feature) {
+    return Err(Error::AlreadyAdded(feature.to_string()));
+  }
+  // the templates offer one e2e testing tool at a time
+  let e2e_testing = [Feature::Cypress, Feature::Playwright];
+  if e2e_testing.contains(&feature) {
+    if let Some(existing) = e2e_testing.into_iter().find(|&other| has_feature(&current, other)) {
+      return Err(Error::ConflictingFeature(feature.to_string(), existing.to_string()));
+    }
+  }
+  let options = with_feature(&current, feature);
+
+  // render the new layers on top of the files they merge into
+  let mut rendered = MemoryFs::new();
+  for name in MERGED_FILES {
+    if let Some(contents) = fs.read(&root.join(name))? {
+      rendered.write(&root.join(name), &contents)?;
+    }
+  }
+
+  let manifest = Manifest::builtin()?;
+  let current_layers = manifest.resolve(&current)?;
+  for path in manifest.resolve(&options)? {
+    // the entry file is patched below instead
+    if current_layers.contains(&path) || path.starts_with("entry/") {
+      continue;
+    }
+    let layer = Layer::from_path(path).ok_or_else(|| Error::TemplateMissing(path.to_string()))?;
+    render_template(get_template(layer), root, &options, &mut rendered)?;
+  }
+
+  if feature == Feature::Eslint {
+    let needs_cypress = matches!(options.needs_e2e_testing, E2eTesting::Cypress);
+    RenderEslint {
+      root: root.to_path_buf(),
+      needs_typescript: options.needs_type_script,
+      needs_cypress,
+      needs_cypress_ct: needs_cypress && !options.needs_vitest,
+      needs_prettier: false,
+      style_guide: options.eslint_style.clone(),
+    }
+    .render(&mut rendered)?;
+  }
+
+  if options.needs_type_script {
+    convert_to_typescript(root, &mut rendered)?;
+  } else {
+    remove_typescript_files(root, &mut rendered)?;
+  }
+
+  let mut report = ChangeReport::default();
+  for (path, contents) in rendered.iter() {
+    let is_merged = path
+      .file_name()
+      .is_some_and(|name| MERGED_FILES.iter().any(|merged| name == *merged));
+    match fs.read(path)? {
+      None => report.created.push(path.clone()),
+      Some(existing) if existing == *contents => continue,
+      Some(_) if is_merged => report.updated.push(path.clone()),
+      Some(_) => {
+        report.conflicts.push(path.clone());
+        continue;
+      }
+    }
+    fs.write(path, contents)?;
+  }
+
+  if let Some(plugin) = entry_plugin(feature) {
+    let entry = ["src/main.ts", "src/main.js"]
+      .iter()
+      .map(|entry| root.join(entry))
+      .find(|entry| fs.is_file(entry));
+    if let Some(entry) = entry {
+      let contents = fs.read_to_string(&entry)?.unwrap_or_default();
+      match patch_entry(&contents, &plugin) {
+        Some(patched) => {
+          fs.write(&entry, patched.as_bytes())?;
+          report.updated.push(entry);
+        }
+        None => report.conflicts.push(entry),
+      }
+    }
+  }
+
+  Ok(report)
+}
+
+/// Reads the features of a generated project from its `package.json`.
+pub fn detect_options(root: &Path, fs: &dyn FileSystem) -> Result<ScaffoldOptions> {
+  let pkg = fs.read(&root.join("package.json"))?.ok_or_else(|| {
+    Error::Io(io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("{:?} has no package.json, run this inside a generated project", root),
+    ))
+  })?;
+  let pkg: Value = serde_json::from_slice(&pkg)?;
+  let has_dependency = |name: &str| {
+    ["dependencies", "devDependencies"]
+      .iter()
+      .any(|key| pkg[key].get(name).is_some())
+  };
+
+  let needs_e2e_testing = if has_dependency("cypress") {
+    E2eTesting::Cypress
+  } else if has_dependency("@playwright/test") {
+    E2eTesting::Playwright
+  } else {
+    E2eTesting::None
+  };
+
+  let package_name = pkg["name"].as_str().unwrap_or("vue-project").to_string();
+  Ok(ScaffoldOptions {
+    project_name: root
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_else(|| package_name.clone()),
+    package_name,
+    needs_type_script: fs.is_file(&root.join("tsconfig.json")),
+    needs_jsx: has_dependency("@vitejs/plugin-vue-jsx"),
+    needs_router: has_dependency("vue-router"),
+    needs_pinia: has_dependency("pinia"),
+    needs_vitest: has_dependency("vitest"),
+    needs_e2e_testing,
+    needs_eslint: has_dependency("eslint"),
+    needs_prettier: has_dependency("prettier"),
+    ..Default::default()
+  })
+}
+
+fn has_feature(options: &ScaffoldOptions, feature: Feature) -> bool {
+  match feature {
+    Feature::Jsx => options.needs_jsx,
+    Feature::Router => options.needs_router,
+    Feature::Pinia => options.needs_pinia,
+    Feature::Vitest => options.needs_vitest,
+    Feature::Cypress => matches!(options.needs_e2e_testing, E2eTesting::Cypress),
+    Feature::Playwright => matches!(options.needs_e2e_testing, E2eTesting::Playwright),
+    Feature::Eslint => options.needs_eslint,
+  }
+}
+
+fn with_feature(options: &ScaffoldOptions, feature: Feature) -> ScaffoldOptions {
+  let mut options = options.clone();
+  match feature {
+    Feature::Jsx => options.needs_jsx = true,
+    Feature::Router => options.needs_router = true,
+    Feature::Pinia => options.needs_pinia = true,
+    Feature::Vitest => options.needs_vitest = true,
+    Feature::Cypress => options.needs_e2e_testing = E2eTesting::Cypress,
+    Feature::Playwright => options.needs_e2e_testing = E2eTesting::Playwright,
+    Feature::Eslint => options.needs_eslint = true,
+  }
+  options
+}
+
+/// How the entry file installs a plugin.
+struct EntryPlugin {
+  import: &'static str,
+  /// The import the new one goes after.
+  after: &'static str,
+  install: &'static str,
+}
+
+fn entry_plugin(feature: Feature) -> Option<EntryPlugin> {
+  match feature {
+    Feature::Router => Some(EntryPlugin {
+      import: "import router from './router'",
+      after: "import App from './App.vue'",
+      install: "app.use(router)",
+    }),
+    Feature::Pinia => Some(EntryPlugin {
+      import: "import { createPinia } from 'pinia'",
+      after: "import { createApp } from 'vue'",
+      install: "app.use(createPinia())",
+    }),
+    _ => None,
+  }
+}
+
+/// Adds the plugin's import and `app.use()` call to an entry file shaped like the entry
+/// templates, or returns `None` if it was changed too much to patch.
+fn patch_entry(contents: &str, plugin: &EntryPlugin) -> Option<String> {
+  let mut lines = contents.lines().map(str::to_string).collect::<Vec<_>>();
+
+  let import_index = lines
+    .iter()
+    .position(|line| line.starts_with(plugin.after))
+    .or_else(|| lines.iter().rposition(|line| line.starts_with("import ")))?;
+  lines.insert(import_index + 1, plugin.import.to_string());
+
+  if let Some(index) = lines
+    .iter()
+    .position(|line| line.starts_with("createApp(App).mount("))
+  {
+    let mount = lines[index].replacen("createApp(App)", "app", 1);
+    lines.splice(
+      index..=index,
+      [
+        "const app = createApp(App)".to_string(),
+        String::new(),
+        plugin.install.to_string(),
+        String::new(),
+        mount,
+      ],
+    );
+  } else if let Some(index) = lines.iter().rposition(|line| line.starts_with("app.use(")) {
+    lines.insert(index + 1, plugin.install.to_string());
+  } else {
+    let index = lines.iter().position(|line| line.starts_with("app.mount("))?;
+    lines.splice(index..index, [plugin.install.to_string(), String::new()]);
+  }
+
+  Some(lines.join("\n") + "\n")
+}
+
+#[test]
+fn test_patch_entry() {
+  let entry = |layer: Layer| {
+    let file = get_template(layer).get_file(format!("{}/src/main.js", layer.path()));
+    String::from_utf8_lossy(file.unwrap().contents()).into_owned()
+  };
+  let router = entry_plugin(Feature::Router).unwrap();
+  let pinia = entry_plugin(Feature::Pinia).unwrap();
+
+  assert_eq!(
+    patch_entry(&entry(Layer::EntryDefault), &router).unwrap(),
+    entry(Layer::EntryRouter)
+  );
+  assert_eq!(
+    patch_entry(&entry(Layer::EntryDefault), &pinia).unwrap(),
+    entry(Layer::EntryPinia)
+  );
+  let both = patch_entry(&entry(Layer::EntryPinia), &router).unwrap();
+  assert!(both.contains("import router from './router'\n"));
+  assert!(both.contains("app.use(createPinia())\napp.use(router)\n\napp.mount('#app')"));
+
+  assert_eq!(patch_entry("export {}\n", &router), None);
+}
+
+#[test]
+fn test_add_feature() {
+  use crate::scaffold::scaffold_into;
+
+  let root = Path::new("/vue-project");
+  let mut fs = MemoryFs::new();
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    ..Default::default()
+  };
+  scaffold_into(&options, root, &mut fs).unwrap();
+  fs.write(&root.join("src/App.vue"), b"<template>mine</template>\n")
+    .unwrap();
+
+  let report = add_feature(root, Feature::Router, &mut fs).unwrap();
+  assert!(report.created.contains(&root.join("src/router/index.ts")));
+  assert!(report.updated.contains(&root.join("package.json")));
+  assert!(report.updated.contains(&root.join("src/main.ts")));
+  assert!(report.conflicts.contains(&root.join("src/App.vue")));
+  assert_eq!(
+    fs.read_to_string(&root.join("src/App.vue")).unwrap().unwrap(),
+    "<template>mine</template>\n"
+  );
+  assert!(!fs.is_file(&root.join("src/router/index.js")));
+
+  let main = fs.read_to_string(&root.join("src/main.ts")).unwrap().unwrap();
+  assert!(main.contains("app.use(router)"));
+  let pkg = fs.read_to_string(&root.join("package.json")).unwrap().unwrap();
+  assert!(pkg.contains(r#""vue-router""#));
+  assert!(pkg.contains(r#""build": "run-p type-check build-only""#));
+
+  let options = detect_options(root, &fs).unwrap();
+  assert!(options.needs_type_script && options.needs_router && !options.needs_pinia);
+  assert!(matches!(
+    add_feature(root, Feature::Router, &mut fs),
+    Err(Error::AlreadyAdded(_))
+  ));
+
+  let report = add_feature(root, Feature::Playwright, &mut fs).unwrap();
+  assert!(report.created.contains(&root.join("playwright.config.ts")));
+  assert!(report.created.contains(&root.join("e2e/tsconfig.json")));
+  assert!(report.updated.contains(&root.join(".gitignore")));
+  assert!(report.conflicts.is_empty());
+  assert!(matches!(
+    add_feature(root, Feature::Cypress, &mut fs),
+    Err(Error::ConflictingFeature(..))
+  ));
+  assert!(!fs.is_file(&root.join("cypress.config.ts")));
+}
+
+#[test]
+fn test_add_feature_invalid_package_json() {
+  let root = Path::new("/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(
+    &root.join("package.json"),
+    br#"{"name":"vue-project","devDependencies":null}"#,
+  )
+  .unwrap();
+  fs.write(&root.join("src/main.js"), b"").unwrap();
+
+  assert!(matches!(
+    add_feature(root, Feature::Pinia, &mut fs),
+    Err(Error::Merge(_))
+  ));
+}
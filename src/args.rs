@@ -1,9 +1,19 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use create_vue_rust::{
+  E2eTesting, Feature, Generator, PackageManager, ScaffoldOptions, StyleGuide,
+};
 
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
+  #[command(subcommand)]
+  pub command: Option<Command>,
+
   pub project_name: Option<String>,
 
   #[arg(long)]
@@ -30,15 +40,186 @@ pub struct Args {
   #[arg(long)]
   pub cypress: bool,
 
-  #[arg(long)]
+  #[arg(long, conflicts_with_all = ["cypress", "tests"])]
   pub playwright: bool,
 
   #[arg(long)]
   pub eslint: bool,
-  ///
+
   #[arg(alias = "eslint-with-prettier", long)]
   pub eslint_with_prettier: bool,
 
+  /// ESLint style guide
+  #[arg(long, value_enum)]
+  pub eslint_style: Option<StyleGuide>,
+
+  /// Template directory to render after the built-in ones, can be repeated
+  ///
+  /// Placeholders like `<%= projectName %>` are filled in, write `<%%` for a literal `<%`.
+  #[arg(long = "template", value_name = "PATH")]
+  pub templates: Vec<PathBuf>,
+
+  /// Remove the existing files of a non-empty target directory, except .git
   #[arg(long)]
   pub force: bool,
+
+  /// Where to write the backup of the files --force removes, defaults to the parent directory
+  #[arg(long, value_name = "PATH", conflicts_with = "no_backup")]
+  pub backup_dir: Option<PathBuf>,
+
+  /// Remove existing files without writing a backup archive first
+  #[arg(long)]
+  pub no_backup: bool,
+
+  /// Keep existing files that the templates would change
+  #[arg(long, conflicts_with_all = ["force", "overwrite_existing"])]
+  pub skip_existing: bool,
+
+  /// Overwrite existing files that the templates would change, keeping all others
+  #[arg(long, conflicts_with = "force")]
+  pub overwrite_existing: bool,
+
+  /// Deep-merge existing JSON files, such as package.json, with the templates
+  #[arg(long, conflicts_with = "force")]
+  pub merge_json: bool,
+
+  /// Initialize a git repository with an initial commit
+  #[arg(long, conflicts_with = "no_git")]
+  pub git: bool,
+
+  /// Do not initialize a git repository
+  #[arg(long)]
+  pub no_git: bool,
+
+  /// The message of the initial commit
+  #[arg(long, value_name = "MESSAGE", default_value = "Initial commit")]
+  pub commit_message: String,
+
+  /// The package manager for the next steps, detected from the one running this tool by default
+  #[arg(long, value_enum)]
+  pub package_manager: Option<PackageManager>,
+
+  /// Install the dependencies with the package manager after scaffolding
+  #[arg(long)]
+  pub install: bool,
+
+  /// Accept the defaults for every question, implied when stdin is not a terminal
+  #[arg(short, long)]
+  pub yes: bool,
+
+  /// Print the files that would be generated without writing anything
+  #[arg(long)]
+  pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Add a feature to an existing project, reporting files it would overwrite
+  Add {
+    #[arg(value_enum)]
+    feature: Feature,
+
+    /// The project directory
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+  },
+
+  /// Generate a component, a view or a store in an existing project
+  Generate {
+    #[arg(value_enum)]
+    generator: Generator,
+
+    /// The name, e.g. UserCard, settings or cart
+    name: String,
+
+    /// The project directory
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+  },
+
+  /// Render every feature combination and compare it with the golden snapshots
+  VerifyTemplates {
+    /// Directory holding the snapshots
+    #[arg(long, default_value = "snapshots")]
+    dir: PathBuf,
+
+    /// Rewrite the snapshots instead of comparing against them
+    #[arg(long)]
+    update: bool,
+  },
+}
+
+impl Args {
+  pub fn is_feature_flags_used(&self) -> bool {
+    self.default
+      | self.ts
+      | self.jsx
+      | self.router
+      | self.pinia
+      | self.tests
+      | self.vitest
+      | self.cypress
+      | self.playwright
+      | self.eslint
+      | self.eslint_with_prettier
+      | self.eslint_style.is_some()
+  }
+
+  /// Derives the feature set from the flags alone, the way upstream create-vue does:
+  /// `--tests` means vitest plus cypress and `--eslint-with-prettier` enables both tools.
+  pub fn resolve_options(&self, project_name: String, package_name: String) -> ScaffoldOptions {
+    let needs_e2e_testing = if self.cypress | self.tests {
+      E2eTesting::Cypress
+    } else if self.playwright {
+      E2eTesting::Playwright
+    } else {
+      E2eTesting::None
+    };
+
+    ScaffoldOptions {
+      project_name,
+      package_name,
+      needs_type_script: self.ts,
+      needs_jsx: self.jsx,
+      needs_router: self.router,
+      needs_pinia: self.pinia,
+      needs_vitest: self.vitest | self.tests,
+      needs_e2e_testing,
+      needs_eslint: self.eslint | self.eslint_with_prettier | self.eslint_style.is_some(),
+      needs_prettier: self.eslint_with_prettier,
+      eslint_style: self.eslint_style.clone().unwrap_or(StyleGuide::Default),
+      templates: self.templates.clone(),
+      package_manager: self.package_manager.unwrap_or_else(PackageManager::detect),
+    }
+  }
+}
+
+#[test]
+fn test_resolve_options() {
+  let args =
+    Args::try_parse_from(["create-vue-rust", "app", "--tests", "--eslint-with-prettier"]).unwrap();
+  assert!(args.is_feature_flags_used());
+  let options = args.resolve_options("app".to_string(), "app".to_string());
+  assert!(options.needs_vitest);
+  assert!(matches!(options.needs_e2e_testing, E2eTesting::Cypress));
+  assert!(options.needs_eslint);
+  assert!(options.needs_prettier);
+
+  let args = Args::try_parse_from([
+    "create-vue-rust",
+    "app",
+    "--playwright",
+    "--template",
+    "house",
+    "--template",
+    "extra",
+  ])
+  .unwrap();
+  let options = args.resolve_options("app".to_string(), "app".to_string());
+  assert_eq!(options.templates, [PathBuf::from("house"), PathBuf::from("extra")]);
+  assert!(!options.needs_vitest);
+  assert!(matches!(options.needs_e2e_testing, E2eTesting::Playwright));
+
+  assert!(Args::try_parse_from(["create-vue-rust", "--cypress", "--playwright"]).is_err());
+  assert!(Args::try_parse_from(["create-vue-rust", "--tests", "--playwright"]).is_err());
 }
@@ -0,0 +1,238 @@
+use std::{
+  io::Write,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use tar::{EntryType, Header};
+
+use crate::{error::Result, vfs::FileSystem};
+
+/// Archives the files of `target` that emptying it would remove into a timestamped
+/// `<name>-backup-<YYYYMMDD-HHMMSS>.tar.gz` in `backup_dir`, and returns the archive's path.
+/// A counter is appended when that name is taken, so earlier backups are never overwritten.
+///
+/// Symbolic links are stored as links without being followed, and every entry keeps its
+/// permissions. `.git` is kept by the scaffolder and `node_modules` can be reinstalled, so
+/// neither is archived. Returns `None` when there is nothing to back up.
+pub fn backup_dir(
+  fs: &mut dyn FileSystem,
+  target: &Path,
+  backup_dir: &Path,
+  now: SystemTime,
+) -> Result<Option<PathBuf>> {
+  let name = target
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_else(|| "project".to_string());
+  let seconds = now.duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+
+  let mut entries = vec![];
+  collect_entries(fs, target, &mut entries)?;
+  if !entries.iter().any(|(_, kind)| !matches!(kind, EntryKind::Dir)) {
+    return Ok(None);
+  }
+
+  let mut archive = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+  for (path, kind) in entries {
+    let entry = Path::new(&name).join(path.strip_prefix(target).unwrap_or(&path));
+    let mut header = Header::new_gnu();
+    header.set_mtime(seconds);
+    header.set_mode(fs.mode(&path)?);
+    match kind {
+      EntryKind::Dir => {
+        header.set_entry_type(EntryType::Directory);
+        header.set_size(0);
+        header.set_cksum();
+        archive.append_data(&mut header, entry, std::io::empty())?;
+      }
+      EntryKind::Symlink(link) => {
+        header.set_entry_type(EntryType::Symlink);
+        header.set_size(0);
+        archive.append_link(&mut header, entry, link)?;
+      }
+      EntryKind::File => {
+        let contents = fs.read(&path)?.unwrap_or_default();
+        header.set_size(contents.len() as u64);
+        header.set_cksum();
+        archive.append_data(&mut header, entry, contents.as_slice())?;
+      }
+    }
+  }
+  let mut encoder = archive.into_inner()?;
+  encoder.flush()?;
+  let bytes = encoder.finish()?;
+
+  let stem = format!("{}-backup-{}", name, timestamp(seconds));
+  let mut archive_path = backup_dir.join(format!("{}.tar.gz", stem));
+  let mut counter = 1;
+  while fs.is_file(&archive_path) || fs.read_link(&archive_path)?.is_some() {
+    counter += 1;
+    archive_path = backup_dir.join(format!("{}-{}.tar.gz", stem, counter));
+  }
+  fs.write(&archive_path, &bytes)?;
+  Ok(Some(archive_path))
+}
+
+enum EntryKind {
+  Dir,
+  File,
+  Symlink(PathBuf),
+}
+
+/// Walks `dir` without following symbolic links, so a link to a parent is archived once.
+fn collect_entries(
+  fs: &dyn FileSystem,
+  dir: &Path,
+  entries: &mut Vec<(PathBuf, EntryKind)>,
+) -> Result<()> {
+  if !fs.is_dir(dir) {
+    return Ok(());
+  }
+  for path in fs.read_dir(dir)? {
+    if path
+      .file_name()
+      .is_some_and(|name| name == ".git" || name == "node_modules")
+    {
+      continue;
+    }
+    if let Some(link) = fs.read_link(&path)? {
+      entries.push((path, EntryKind::Symlink(link)));
+    } else if fs.is_dir(&path) {
+      entries.push((path.clone(), EntryKind::Dir));
+      collect_entries(fs, &path, entries)?;
+    } else {
+      entries.push((path, EntryKind::File));
+    }
+  }
+  Ok(())
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC, from Howard Hinnant's `civil_from_days`.
+fn timestamp(seconds: u64) -> String {
+  let days = (seconds / 86400) as i64;
+  let time = seconds % 86400;
+
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let day_of_era = z.rem_euclid(146097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  format!(
+    "{:04}{:02}{:02}-{:02}{:02}{:02}",
+    year,
+    month,
+    day,
+    time / 3600,
+    time % 3600 / 60,
+    time % 60
+  )
+}
+
+#[test]
+fn test_timestamp() {
+  assert_eq!(timestamp(0), "19700101-000000");
+  assert_eq!(timestamp(951782400 + 3723), "20000229-010203");
+  assert_eq!(timestamp(1671494400), "20221220-000000");
+}
+
+#[test]
+fn test_backup_dir() {
+  use std::{io::Read, time::Duration};
+
+  use flate2::read::GzDecoder;
+
+  use crate::vfs::MemoryFs;
+
+  let target = Path::new("/work/my-app");
+  let mut fs = MemoryFs::new();
+  fs.write(&target.join(".git/HEAD"), b"ref").unwrap();
+  let now = UNIX_EPOCH + Duration::from_secs(1671494400);
+  assert_eq!(backup_dir(&mut fs, target, Path::new("/work"), now).unwrap(), None);
+
+  fs.write(&target.join("notes.md"), b"my work").unwrap();
+  fs.write(&target.join("node_modules/vue/index.js"), b"").unwrap();
+  let archive = backup_dir(&mut fs, target, Path::new("/work"), now)
+    .unwrap()
+    .unwrap();
+  assert_eq!(archive, Path::new("/work/my-app-backup-20221220-000000.tar.gz"));
+
+  let bytes = fs.read(&archive).unwrap().unwrap();
+  let mut archive = tar::Archive::new(GzDecoder::new(bytes.as_slice()));
+  let mut entries = vec![];
+  for entry in archive.entries().unwrap() {
+    let mut entry = entry.unwrap();
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).unwrap();
+    entries.push((entry.path().unwrap().display().to_string(), contents));
+  }
+  assert_eq!(entries, [("my-app/notes.md".to_string(), "my work".to_string())]);
+
+  // a second backup in the same second gets its own name
+  let archive = backup_dir(&mut fs, target, Path::new("/work"), now)
+    .unwrap()
+    .unwrap();
+  assert_eq!(archive, Path::new("/work/my-app-backup-20221220-000000-2.tar.gz"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_backup_dir_keeps_links_and_modes() {
+  use std::{fs, os::unix::fs::PermissionsExt};
+
+  use flate2::read::GzDecoder;
+
+  use crate::vfs::DiskFs;
+
+  let work = std::env::temp_dir().join("create-vue-rust-backup");
+  let _ = fs::remove_dir_all(&work);
+  let target = work.join("my-app");
+  fs::create_dir_all(target.join("scripts")).unwrap();
+  fs::write(target.join("scripts/deploy.sh"), "#!/bin/sh\n").unwrap();
+  fs::set_permissions(
+    target.join("scripts/deploy.sh"),
+    fs::Permissions::from_mode(0o755),
+  )
+  .unwrap();
+  std::os::unix::fs::symlink(".", target.join("loop")).unwrap();
+  std::os::unix::fs::symlink("scripts/deploy.sh", target.join("deploy")).unwrap();
+
+  let archive = backup_dir(&mut DiskFs, &target, &work, SystemTime::now())
+    .unwrap()
+    .unwrap();
+
+  let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(archive).unwrap()));
+  let mut entries = vec![];
+  for entry in archive.entries().unwrap() {
+    let entry = entry.unwrap();
+    let header = entry.header();
+    entries.push((
+      entry.path().unwrap().display().to_string(),
+      header.entry_type(),
+      header.mode().unwrap(),
+      entry.link_name().unwrap().map(|link| link.display().to_string()),
+    ));
+  }
+  entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+  assert_eq!(entries.len(), 4);
+  assert_eq!(entries[0].0, "my-app/deploy");
+  assert_eq!(entries[0].1, EntryType::Symlink);
+  assert_eq!(entries[0].3.as_deref(), Some("scripts/deploy.sh"));
+  assert_eq!(entries[1].0, "my-app/loop");
+  assert_eq!(entries[1].3.as_deref(), Some("."));
+  assert_eq!(entries[2].0, "my-app/scripts");
+  assert_eq!(entries[2].1, EntryType::Directory);
+  assert_eq!(
+    (entries[3].0.as_str(), entries[3].2),
+    ("my-app/scripts/deploy.sh", 0o755)
+  );
+
+  fs::remove_dir_all(&work).unwrap();
+}
@@ -0,0 +1,255 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use similar::TextDiff;
+
+use crate::{
+  add::ChangeReport,
+  error::Result,
+  scaffold::{scaffold_into, ScaffoldOptions},
+  vfs::{FileSystem, MemoryFs},
+};
+
+/// A rendered file that already exists in the target with different contents.
+#[derive(Debug)]
+pub struct Conflict {
+  pub path: PathBuf,
+  pub existing: Vec<u8>,
+  pub rendered: Vec<u8>,
+}
+
+impl Conflict {
+  /// Whether both sides are JSON objects, which [`Resolution::Merge`] can combine.
+  pub fn is_json(&self) -> bool {
+    let is_object = |contents: &[u8]| {
+      serde_json::from_slice::<Value>(contents).is_ok_and(|value| value.is_object())
+    };
+    self.path.extension().is_some_and(|ext| ext == "json")
+      && is_object(&self.existing)
+      && is_object(&self.rendered)
+  }
+
+  /// A unified diff from the existing file to the rendered one.
+  pub fn diff(&self) -> String {
+    let existing = String::from_utf8_lossy(&self.existing);
+    let rendered = String::from_utf8_lossy(&self.rendered);
+    let diff = TextDiff::from_lines(existing.as_ref(), rendered.as_ref());
+    diff
+      .unified_diff()
+      .context_radius(2)
+      .header("existing", "template")
+      .to_string()
+  }
+
+  /// Deep-merges the rendered JSON into the existing file. Values that are already set in the
+  /// existing file win, so only new keys and array items are added.
+  pub fn merged(&self) -> Result<Vec<u8>> {
+    let existing: Value = serde_json::from_slice(&self.existing)?;
+    let rendered: Value = serde_json::from_slice(&self.rendered)?;
+    let value = merge_new(&rendered, &existing);
+    Ok(serde_json::to_string_pretty(&value)?.into_bytes())
+  }
+}
+
+/// Like [`crate::render::merge`], but leaves out the array items `base` already has, so e.g.
+/// `include` or `extends` don't list a path twice.
+fn merge_new(base: &Value, other: &Value) -> Value {
+  match (base, other) {
+    (Value::Object(base), Value::Object(other)) => {
+      let mut merged = base.clone();
+      for (key, value) in other {
+        let value = match base.get(key) {
+          Some(base) => merge_new(base, value),
+          None => value.clone(),
+        };
+        merged.insert(key.clone(), value);
+      }
+      Value::Object(merged)
+    }
+    (Value::Array(base), Value::Array(other)) => {
+      let mut merged = base.clone();
+      for item in other {
+        if !merged.contains(item) {
+          merged.push(item.clone());
+        }
+      }
+      Value::Array(merged)
+    }
+    _ => other.clone(),
+  }
+}
+
+/// What to do with a [`Conflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+  Keep,
+  Overwrite,
+  Merge,
+}
+
+/// The files to write into a non-empty target, decided before anything is written.
+#[derive(Debug, Default)]
+pub struct MergePlan {
+  writes: Vec<(PathBuf, Vec<u8>)>,
+  kept: Vec<PathBuf>,
+}
+
+/// Renders a project in memory and decides file by file how it goes into an existing `target`.
+///
+/// New files are always written and identical ones skipped. Every other file is passed to
+/// `resolve`, e.g. to ask the user. Nothing is written until [`MergePlan::apply`].
+pub fn plan_scaffold(
+  options: &ScaffoldOptions,
+  target: &Path,
+  fs: &dyn FileSystem,
+  mut resolve: impl FnMut(&Conflict) -> Result<Resolution>,
+) -> Result<MergePlan> {
+  let mut rendered = MemoryFs::new();
+  scaffold_into(options, target, &mut rendered)?;
+
+  let mut plan = MergePlan::default();
+  for (path, contents) in rendered.iter() {
+    let existing = match fs.read(path)? {
+      None => {
+        plan.writes.push((path.clone(), contents.clone()));
+        continue;
+      }
+      Some(existing) if existing == *contents => continue,
+      Some(existing) => existing,
+    };
+
+    let conflict = Conflict {
+      path: path.clone(),
+      existing,
+      rendered: contents.clone(),
+    };
+    match resolve(&conflict)? {
+      Resolution::Keep => plan.kept.push(conflict.path),
+      Resolution::Overwrite => plan.writes.push((conflict.path, conflict.rendered)),
+      Resolution::Merge => {
+        let merged = conflict.merged()?;
+        plan.writes.push((conflict.path, merged));
+      }
+    }
+  }
+  Ok(plan)
+}
+
+impl MergePlan {
+  /// Writes the planned files. If a write fails, the files written so far are restored.
+  pub fn apply(self, fs: &mut dyn FileSystem) -> Result<ChangeReport> {
+    let mut report = ChangeReport {
+      conflicts: self.kept,
+      ..Default::default()
+    };
+    let mut originals: Vec<(PathBuf, Option<Vec<u8>>)> = vec![];
+
+    for (path, contents) in self.writes {
+      let original = fs.read(&path)?;
+      if let Err(err) = fs.write(&path, &contents) {
+        for (path, original) in originals.into_iter().rev() {
+          match original {
+            Some(original) => fs.write(&path, &original)?,
+            None => fs.remove_file(&path)?,
+          }
+        }
+        return Err(err);
+      }
+
+      if original.is_some() {
+        report.updated.push(path.clone());
+      } else {
+        report.created.push(path.clone());
+      }
+      originals.push((path, original));
+    }
+
+    Ok(report)
+  }
+}
+
+#[test]
+fn test_plan_scaffold() {
+  let target = Path::new("/repo");
+  let mut fs = MemoryFs::new();
+  fs.write(&target.join("README.md"), b"# My repo\n").unwrap();
+  fs.write(&target.join("index.html"), b"<html></html>\n").unwrap();
+  fs.write(
+    &target.join("package.json"),
+    br#"{"name": "repo", "private": true, "scripts": {"build": "make"}}"#,
+  )
+  .unwrap();
+
+  let options = ScaffoldOptions::default();
+  let mut conflicts = vec![];
+  let plan = plan_scaffold(&options, target, &fs, |conflict| {
+    conflicts.push(conflict.path.clone());
+    Ok(if conflict.is_json() {
+      Resolution::Merge
+    } else {
+      if conflict.path.ends_with("index.html") {
+        assert!(conflict.diff().contains("-<html></html>"));
+      }
+      Resolution::Keep
+    })
+  })
+  .unwrap();
+  assert_eq!(
+    conflicts,
+    [
+      target.join("README.md"),
+      target.join("index.html"),
+      target.join("package.json")
+    ]
+  );
+
+  let report = plan.apply(&mut fs).unwrap();
+  assert_eq!(
+    report.conflicts,
+    [target.join("README.md"), target.join("index.html")]
+  );
+  assert_eq!(report.updated, [target.join("package.json")]);
+  assert!(report.created.contains(&target.join("src/main.js")));
+
+  assert_eq!(
+    fs.read_to_string(&target.join("README.md")).unwrap().unwrap(),
+    "# My repo\n"
+  );
+  assert_eq!(
+    fs.read_to_string(&target.join("index.html")).unwrap().unwrap(),
+    "<html></html>\n"
+  );
+  let pkg: Value =
+    serde_json::from_slice(&fs.read(&target.join("package.json")).unwrap().unwrap()).unwrap();
+  assert_eq!(pkg["name"], "repo");
+  assert_eq!(pkg["scripts"]["build"], "make");
+  assert_eq!(pkg["scripts"]["dev"], "vite");
+  assert!(pkg["dependencies"]["vue"].is_string());
+}
+
+#[test]
+fn test_merge_into_itself() {
+  let tsconfig = serde_json::to_string_pretty(&serde_json::json!({
+    "extends": ["@vue/tsconfig/tsconfig.web.json"],
+    "include": ["env.d.ts", "src/**/*", "src/**/*.vue"],
+    "compilerOptions": { "paths": { "@/*": ["./src/*"] } }
+  }))
+  .unwrap()
+  .into_bytes();
+  let conflict = Conflict {
+    path: PathBuf::from("/repo/tsconfig.json"),
+    existing: tsconfig.clone(),
+    rendered: tsconfig.clone(),
+  };
+  assert_eq!(conflict.merged().unwrap(), tsconfig);
+
+  let conflict = Conflict {
+    rendered: br#"{"include": ["env.d.ts", "src/**/*.ts"]}"#.to_vec(),
+    ..conflict
+  };
+  let merged: Value = serde_json::from_slice(&conflict.merged().unwrap()).unwrap();
+  assert_eq!(
+    merged["include"],
+    serde_json::json!(["env.d.ts", "src/**/*.ts", "src/**/*", "src/**/*.vue"])
+  );
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use crate::{error::Result, vfs::FileSystem};
+
+/// Converts a rendered JavaScript project into a TypeScript one.
+///
+/// Every remaining `.js` file is dropped when a `.ts` sibling exists and renamed to `.ts`
+/// otherwise. `jsconfig.json` is removed, because the tsconfig templates replace it.
+pub fn convert_to_typescript(root: &Path, fs: &mut dyn FileSystem) -> Result<()> {
+  for path in project_files(root, fs)? {
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+
+    if file_name == "jsconfig.json" {
+      fs.remove_file(&path)?;
+    } else if file_name.ends_with(".js") {
+      let ts_path = path.with_extension("ts");
+      if fs.is_file(&ts_path) {
+        fs.remove_file(&path)?;
+      } else {
+        fs.rename(&path, &ts_path)?;
+      }
+    }
+  }
+
+  let index_html_path = root.join("index.html");
+  if let Some(content) = fs.read_to_string(&index_html_path)? {
+    let content = content.replace("src/main.js", "src/main.ts");
+    fs.write(&index_html_path, content.as_bytes())?;
+  }
+
+  Ok(())
+}
+
+/// Removes the `.ts` variants shipped next to their `.js` counterparts, e.g. `cypress.config.ts`.
+pub fn remove_typescript_files(root: &Path, fs: &mut dyn FileSystem) -> Result<()> {
+  for path in project_files(root, fs)? {
+    if path.extension().is_some_and(|ext| ext == "ts") {
+      fs.remove_file(&path)?;
+    }
+  }
+  Ok(())
+}
+
+fn project_files(root: &Path, fs: &dyn FileSystem) -> Result<Vec<std::path::PathBuf>> {
+  let files = fs.files(root)?;
+  Ok(
+    files
+      .into_iter()
+      .filter(|path| {
+        !path
+          .strip_prefix(root)
+          .unwrap_or(path)
+          .components()
+          .any(|component| {
+            let name = component.as_os_str();
+            name == ".git" || name == "node_modules"
+          })
+      })
+      .collect(),
+  )
+}
+
+#[test]
+fn test_convert_to_typescript() {
+  use crate::vfs::MemoryFs;
+
+  let root = Path::new("/project");
+  let mut fs = MemoryFs::new();
+  let files: [(&str, &str); 5] = [
+    ("index.html", r#"<script src="/src/main.js"></script>"#),
+    ("vite.config.js", ""),
+    ("cypress.config.js", "js"),
+    ("cypress.config.ts", "ts"),
+    ("cypress/e2e/jsconfig.json", "{}"),
+  ];
+  for (name, contents) in files {
+    fs.write(&root.join(name), contents.as_bytes()).unwrap();
+  }
+
+  convert_to_typescript(root, &mut fs).unwrap();
+
+  assert!(!fs.is_file(&root.join("vite.config.js")));
+  assert!(fs.is_file(&root.join("vite.config.ts")));
+  assert!(!fs.is_file(&root.join("cypress.config.js")));
+  assert_eq!(
+    fs.read_to_string(&root.join("cypress.config.ts")).unwrap().unwrap(),
+    "ts"
+  );
+  assert!(!fs.is_file(&root.join("cypress/e2e/jsconfig.json")));
+  assert_eq!(
+    fs.read_to_string(&root.join("index.html")).unwrap().unwrap(),
+    r#"<script src="/src/main.ts"></script>"#
+  );
+}
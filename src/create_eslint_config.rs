@@ -1,14 +1,16 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{fmt::Display, path::Path};
 
-use anyhow::Result;
+use crate::error::Result;
+use clap::ValueEnum;
 use serde_json::{json, Value};
 
 use crate::{
-  editor_config::{create_config, Editorconfilgs},
+  editor_config::{Editorconfilgs, Prettierconfigs},
   render::merge,
+  vfs::FileSystem,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, ValueEnum)]
 pub enum StyleGuide {
   Default,
   Airbnb,
@@ -36,14 +38,14 @@ impl ConfigFiles {
   fn new() -> Self {
     Self {
       editorconfig: ConfigFile::new(".editorconfig".to_string()),
-      eslintrc: ConfigFile::new(".eslintrc".to_string()),
-      prettierrc: ConfigFile::new(".prettierrc".to_string()),
+      eslintrc: ConfigFile::new(".eslintrc.cjs".to_string()),
+      prettierrc: ConfigFile::new(".prettierrc.json".to_string()),
     }
   }
-  pub fn write_to_disk(&self, root: &PathBuf) -> Result<()> {
-    self.editorconfig.write_to_disk(&root)?;
-    self.prettierrc.write_to_disk(&root)?;
-    self.eslintrc.write_to_disk(&root)?;
+  pub fn write_to(&self, root: &Path, fs: &mut dyn FileSystem) -> Result<()> {
+    self.editorconfig.write_to(root, fs)?;
+    self.prettierrc.write_to(root, fs)?;
+    self.eslintrc.write_to(root, fs)?;
     Ok(())
   }
 }
@@ -72,14 +74,10 @@ impl ConfigFile {
       self.1 = Some(value);
     }
   }
-  pub fn write_to_disk(&self, root: &PathBuf) -> Result<()> {
-    match &self.1 {
-      Some(contents) => {
-        let filename = self.0.clone();
-        let path = root.join(filename);
-        std::fs::write(path, contents)?;
-      }
-      _ => {}
+  pub fn write_to(&self, root: &Path, fs: &mut dyn FileSystem) -> Result<()> {
+    if let Some(contents) = &self.1 {
+      let path = root.join(&self.0);
+      fs.write(&path, contents.as_bytes())?;
     }
     Ok(())
   }
@@ -99,7 +97,7 @@ impl EslintConfig {
     let value = json!(
       {
         "root": true,
-        "extends": ["plugin:vue/essential"]
+        "extends": ["plugin:vue/vue3-essential"]
       }
     );
     Self(value)
@@ -156,6 +154,10 @@ impl PkgConfig {
     let map = self.0.as_object().unwrap();
     map.get(key)
   }
+  pub fn get_value(&self) -> &Value {
+    &self.0
+  }
+
   fn has_dependency(&self, name: &str) -> bool {
     let map = self.get("devDependencies").unwrap().as_object().unwrap();
     map.contains_key(name)
@@ -192,13 +194,9 @@ impl CreateConfig {
     pkg.add_dependency("eslint");
     pkg.add_dependency("eslint-plugin-vue");
 
-    match &style_guide {
-      StyleGuide::Default => {}
-      _ => {
-        if has_typescript || needs_prettier {
-          pkg.add_dependency("@rushstack/eslint-patch");
-        }
-      }
+    // the shareable configs resolve their plugins relative to themselves
+    if !matches!(style_guide, StyleGuide::Default) || has_typescript || needs_prettier {
+      pkg.add_dependency("@rushstack/eslint-patch");
     }
 
     let language = if has_typescript {
@@ -227,10 +225,12 @@ impl CreateConfig {
         pkg.add_dependency("@vue/eslint-config-standard");
       }
       (StyleGuide::Airbnb, "typescript") => {
-        eslint_config.add_extend("@vue/eslint-config-airbnb-with-typescript")
+        eslint_config.add_extend("@vue/eslint-config-airbnb-with-typescript");
+        pkg.add_dependency("@vue/eslint-config-airbnb-with-typescript");
       }
       (StyleGuide::Standard, "typescript") => {
-        eslint_config.add_extend("@vue/eslint-config-standard-with-typescript")
+        eslint_config.add_extend("@vue/eslint-config-standard-with-typescript");
+        pkg.add_dependency("@vue/eslint-config-standard-with-typescript");
       }
       _ => {}
     }
@@ -242,19 +242,19 @@ impl CreateConfig {
     }
 
     // merge additional_dependencies
-    match additional_dependencies {
-      Some(dep) => pkg.merge(&dep),
-      _ => {}
+    if let Some(dep) = additional_dependencies {
+      pkg.merge(&dep);
     }
 
-    match additional_config {
-      Some(config) => eslint_config.merge(&config),
-      _ => {}
-    };
+    if let Some(config) = additional_config {
+      eslint_config.merge(&config);
+    }
 
     let mut files = ConfigFiles::new();
 
-    let editor_configs = create_config();
+    files
+      .editorconfig
+      .push_string(Editorconfilgs::new().get(&style_guide));
 
     match &style_guide {
       StyleGuide::Default => {
@@ -270,45 +270,55 @@ impl CreateConfig {
         );
       }
       _ => {
-        let editor_config = Editorconfilgs::new();
-        let config = editor_config.get(&style_guide);
-
-        files.editorconfig.push_string(config);
+        // let eslint-plugin-import resolve the `@` alias from vite.config
+        eslint_config.set_val(
+          "settings".to_string(),
+          json!({ CREATE_ALIAS_SETTING_PLACEHOLDER: null }),
+        );
       }
     }
 
     if pkg.has_dependency("@rushstack/eslint-patch") {
       files.eslintrc.push_string(
-        r#"require('@rushstack/eslint-patch/modern-module-resolution')\n\n"#.to_string(),
+        "require('@rushstack/eslint-patch/modern-module-resolution')\n\n".to_string(),
       );
     }
 
     files.eslintrc.push_string(format!(
       "module.exports = {}\n",
-      stringify_js(&eslint_config, &style_guide)
+      stringify_js(&eslint_config, &style_guide, has_typescript)
     ));
 
     if needs_prettier {
-      let prettier_config = Editorconfilgs::new();
+      let prettier_config = Prettierconfigs::new();
 
-      files
-        .prettierrc
-        .push_string(prettier_config.get(&style_guide));
+      files.prettierrc.push_string(format!(
+        "{}\n",
+        serde_json::to_string_pretty(&prettier_config.get(&style_guide))?
+      ));
     }
 
     Ok((pkg, files))
   }
 }
 
-fn stringify_js(eslint_config: &EslintConfig, style_guide: &StyleGuide) -> String {
-  let result = serde_json::to_string_pretty(&eslint_config.0).unwrap();
+fn stringify_js(
+  eslint_config: &EslintConfig,
+  style_guide: &StyleGuide,
+  has_typescript: bool,
+) -> String {
+  let result = serde_json::to_string_pretty(eslint_config.get_value()).unwrap();
 
+  let suffix = if has_typescript { "-with-typescript" } else { "" };
   let replacer = format!(
-    r#"...require('@vue/eslint-config-{}/createAliasSetting')"#,
-    style_guide
+    r#"...require('@vue/eslint-config-{}{}/createAliasSetting')({{ '@': `${{__dirname}}/src` }})"#,
+    style_guide, suffix
   );
 
-  result.replace(CREATE_ALIAS_SETTING_PLACEHOLDER, &replacer)
+  result.replace(
+    &format!(r#""{}": null"#, CREATE_ALIAS_SETTING_PLACEHOLDER),
+    &replacer,
+  )
 }
 
 fn get_version_map() -> Value {
@@ -328,6 +338,26 @@ fn get_version_map() -> Value {
   })
 }
 
+#[test]
+fn test_create_alias_setting() {
+  let config = CreateConfig {
+    style_guide: StyleGuide::Standard,
+    has_typescript: true,
+    needs_prettier: false,
+    additional_config: None,
+    additional_dependencies: None,
+  };
+  let (pkg, files) = config.create().unwrap();
+  assert!(pkg.has_dependency("@vue/eslint-config-standard-with-typescript"));
+  assert!(pkg.has_dependency("@rushstack/eslint-patch"));
+
+  let eslintrc = files.eslintrc.1.unwrap();
+  assert!(eslintrc.contains(
+    "...require('@vue/eslint-config-standard-with-typescript/createAliasSetting')({ '@': `${__dirname}/src` })"
+  ));
+  assert!(!eslintrc.contains(CREATE_ALIAS_SETTING_PLACEHOLDER));
+}
+
 #[test]
 fn main_test() {
   let config = CreateConfig {
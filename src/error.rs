@@ -0,0 +1,140 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+  /// The user declined a prompt or aborted it.
+  Cancelled,
+  InvalidName(String),
+  /// A question without a default was reached in non-interactive mode.
+  MissingAnswer(String),
+  TargetIsFile(PathBuf),
+  /// The target has files and replacing them was not asked for.
+  TargetNotEmpty(PathBuf),
+  TemplateMissing(String),
+  Io(io::Error),
+  /// A `package.json` or config file could not be parsed or merged.
+  Merge(String),
+  /// Rendered templates no longer match the golden snapshots.
+  SnapshotDrift(usize),
+  /// A template manifest could not be parsed, or one of its conditions is invalid.
+  InvalidManifest(String),
+  /// The feature passed to `add` is already part of the project.
+  AlreadyAdded(String),
+  /// The feature passed to `add`, and the one the project has that it can't be combined with.
+  ConflictingFeature(String, String),
+  /// `generate` needs a feature the project does not have yet.
+  MissingFeature(String),
+  /// The dependencies could not be installed, with the command that failed.
+  Install(String),
+  /// A template file has an invalid placeholder.
+  InvalidTemplate(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+  /// The process exit code for this error, distinct per variant.
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      Error::Io(_) => 1,
+      Error::InvalidName(_) => 2,
+      Error::TargetIsFile(_) => 3,
+      Error::TemplateMissing(_) => 4,
+      Error::Merge(_) => 5,
+      Error::MissingAnswer(_) => 6,
+      Error::SnapshotDrift(_) => 7,
+      Error::InvalidManifest(_) => 8,
+      Error::AlreadyAdded(_) => 9,
+      Error::MissingFeature(_) => 10,
+      Error::Install(_) => 11,
+      Error::InvalidTemplate(_) => 12,
+      Error::TargetNotEmpty(_) => 13,
+      Error::ConflictingFeature(..) => 14,
+      Error::Cancelled => 130,
+    }
+  }
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Error::Cancelled => write!(f, "Operation cancelled"),
+      Error::InvalidName(name) => write!(f, "Invalid package name: {}", name),
+      Error::MissingAnswer(message) => write!(f, "{}", message),
+      Error::TargetIsFile(path) => write!(f, "Target is a file, not a directory: {:?}", path),
+      Error::TargetNotEmpty(path) => write!(f, "Target directory {:?} is not empty", path),
+      Error::TemplateMissing(name) => write!(f, "Template {:?} does not exist", name),
+      Error::Io(err) => write!(f, "{}", err),
+      Error::Merge(message) => write!(f, "Failed to merge config: {}", message),
+      Error::SnapshotDrift(count) => write!(
+        f,
+        "{} feature combinations drifted from the snapshots, rerun with --update if intended",
+        count
+      ),
+      Error::InvalidManifest(message) => write!(f, "Invalid template manifest: {}", message),
+      Error::AlreadyAdded(feature) => write!(f, "{} is already part of this project", feature),
+      Error::ConflictingFeature(feature, existing) => write!(
+        f,
+        "{} can't be added to this project, it already uses {}",
+        feature, existing
+      ),
+      Error::MissingFeature(feature) => write!(
+        f,
+        "This project has no {}, run `create-vue-rust add {}` first",
+        feature, feature
+      ),
+      Error::Install(command) => write!(
+        f,
+        "`{}` failed, the project was created, rerun it in the project directory",
+        command
+      ),
+      Error::InvalidTemplate(message) => write!(f, "Invalid template {}", message),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(err: io::Error) -> Self {
+    Error::Io(err)
+  }
+}
+
+impl From<serde_json::Error> for Error {
+  fn from(err: serde_json::Error) -> Self {
+    Error::Merge(err.to_string())
+  }
+}
+
+#[test]
+fn test_exit_codes_are_distinct() {
+  let errors = [
+    Error::Cancelled,
+    Error::InvalidName("".to_string()),
+    Error::MissingAnswer("".to_string()),
+    Error::TargetIsFile(PathBuf::new()),
+    Error::TargetNotEmpty(PathBuf::new()),
+    Error::TemplateMissing("".to_string()),
+    Error::Io(io::Error::other("")),
+    Error::Merge("".to_string()),
+    Error::SnapshotDrift(0),
+    Error::InvalidManifest("".to_string()),
+    Error::AlreadyAdded("".to_string()),
+    Error::ConflictingFeature("".to_string(), "".to_string()),
+    Error::MissingFeature("".to_string()),
+    Error::Install("".to_string()),
+    Error::InvalidTemplate("".to_string()),
+  ];
+  let mut codes = errors.iter().map(Error::exit_code).collect::<Vec<_>>();
+  codes.sort();
+  codes.dedup();
+  assert_eq!(codes.len(), errors.len());
+}
@@ -0,0 +1,292 @@
+use std::{fmt::Display, path::Path};
+
+use clap::ValueEnum;
+
+use crate::{
+  add::{detect_options, ChangeReport},
+  error::{Error, Result},
+  templates::{get_template, Layer},
+  vfs::FileSystem,
+};
+
+/// What `generate` can create in an existing project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Generator {
+  Component,
+  View,
+  Store,
+}
+
+impl Display for Generator {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Generator::Component => write!(f, "component"),
+      Generator::View => write!(f, "view"),
+      Generator::Store => write!(f, "store"),
+    }
+  }
+}
+
+/// Generates a component, a view or a store named `name` in the project in `root`.
+///
+/// The files follow the project's language and the `<script setup>` and setup store style of
+/// the templates. Views are also registered as a lazy-loaded route in `src/router/index`.
+/// An existing file is reported as a conflict instead of overwritten.
+pub fn generate(
+  root: &Path,
+  generator: Generator,
+  name: &str,
+  fs: &mut dyn FileSystem,
+) -> Result<ChangeReport> {
+  if !is_valid_name(name) {
+    return Err(Error::InvalidName(name.to_string()));
+  }
+
+  let options = detect_options(root, fs)?;
+  let extension = if options.needs_type_script { "ts" } else { "js" };
+  let mut report = ChangeReport::default();
+
+  match generator {
+    Generator::Component => {
+      let name = pascal_case(name);
+      let path = root.join(format!("src/components/{}.vue", name));
+      let contents = component(&name, options.needs_type_script);
+      create(&path, &contents, fs, &mut report)?;
+    }
+    Generator::View => {
+      if !options.needs_router {
+        return Err(Error::MissingFeature("router".to_string()));
+      }
+      let router_path = root.join(format!("src/router/index.{}", extension));
+      let router = fs.read_to_string(&router_path)?.unwrap_or_default();
+
+      let name = pascal_case(name.strip_suffix("View").unwrap_or(name));
+      let view_name = format!("{}View", name);
+      let path = root.join(format!("src/views/{}.vue", view_name));
+      if !create(&path, &view(&name), fs, &mut report)? {
+        return Ok(report);
+      }
+
+      match register_route(&router, &kebab_case(&name), &view_name) {
+        Some(router) => {
+          fs.write(&router_path, router.as_bytes())?;
+          report.updated.push(router_path);
+        }
+        None => report.conflicts.push(router_path),
+      }
+    }
+    Generator::Store => {
+      if !options.needs_pinia {
+        return Err(Error::MissingFeature("pinia".to_string()));
+      }
+      let id = camel_case(name);
+      let path = root.join(format!("src/stores/{}.{}", id, extension));
+      create(&path, &store(&id), fs, &mut report)?;
+    }
+  }
+
+  Ok(report)
+}
+
+/// Writes a new file, or reports a conflict if it already exists. Returns whether it was written.
+fn create(
+  path: &Path,
+  contents: &str,
+  fs: &mut dyn FileSystem,
+  report: &mut ChangeReport,
+) -> Result<bool> {
+  if fs.is_file(path) {
+    report.conflicts.push(path.to_path_buf());
+    return Ok(false);
+  }
+  fs.write(path, contents.as_bytes())?;
+  report.created.push(path.to_path_buf());
+  Ok(true)
+}
+
+fn component(name: &str, needs_type_script: bool) -> String {
+  let script = if needs_type_script {
+    "<script setup lang=\"ts\">\ndefineProps<{\n  msg: string\n}>()\n</script>\n"
+  } else {
+    "<script setup>\ndefineProps({\n  msg: {\n    type: String,\n    required: true\n  }\n})\n</script>\n"
+  };
+  format!(
+    "{}\n<template>\n  <div class=\"{}\">\n    <h1>{{{{ msg }}}}</h1>\n  </div>\n</template>\n",
+    script,
+    kebab_case(name)
+  )
+}
+
+/// Derived from the `AboutView` of the router template.
+fn view(name: &str) -> String {
+  template_file(Layer::CodeRouter, "src/views/AboutView.vue")
+    .replace("about", &kebab_case(name))
+    .replace("This is an", "This is the")
+}
+
+/// Derived from the counter store of the Pinia template.
+fn store(id: &str) -> String {
+  template_file(Layer::ConfigPinia, "src/stores/counter.js")
+    .replace("useCounterStore", &format!("use{}Store", pascal_case(id)))
+    .replace("'counter'", &format!("'{}'", id))
+}
+
+fn template_file(layer: Layer, path: &str) -> String {
+  let file = get_template(layer)
+    .get_file(format!("{}/{}", layer.path(), path))
+    .map(|file| file.contents())
+    .unwrap_or_default();
+  String::from_utf8_lossy(file).into_owned()
+}
+
+/// Appends a lazy-loaded route to the `routes` array of a router shaped like the template's,
+/// or returns `None` if it was changed too much to patch.
+fn register_route(router: &str, route: &str, view_name: &str) -> Option<String> {
+  let mut lines = router.lines().map(str::to_string).collect::<Vec<_>>();
+
+  let start = lines.iter().position(|line| line.trim_start().starts_with("routes: ["))?;
+  let indent = lines[start][..lines[start].len() - lines[start].trim_start().len()].to_string();
+  let end = start
+    + lines[start..]
+      .iter()
+      .position(|line| *line == format!("{}]", indent))?;
+
+  if end > start + 1 && !lines[end - 1].ends_with(',') {
+    lines[end - 1].push(',');
+  }
+  let entry = [
+    "{".to_string(),
+    format!("  path: '/{}',", route),
+    format!("  name: '{}',", route),
+    format!("  component: () => import('../views/{}.vue')", view_name),
+    "}".to_string(),
+  ];
+  lines.splice(
+    end..end,
+    entry.iter().map(|line| format!("{}  {}", indent, line)),
+  );
+
+  Some(lines.join("\n") + "\n")
+}
+
+fn is_valid_name(name: &str) -> bool {
+  name.starts_with(|char: char| char.is_ascii_alphabetic())
+    && name
+      .chars()
+      .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+}
+
+fn words(name: &str) -> Vec<String> {
+  let mut words = vec![];
+  let mut word = String::new();
+  for char in name.chars() {
+    if char == '-' || char == '_' || (char.is_ascii_uppercase() && !word.is_empty()) {
+      if !word.is_empty() {
+        words.push(word.to_lowercase());
+      }
+      word = String::new();
+    }
+    if char != '-' && char != '_' {
+      word.push(char);
+    }
+  }
+  if !word.is_empty() {
+    words.push(word.to_lowercase());
+  }
+  words
+}
+
+fn capitalize(word: &str) -> String {
+  let mut chars = word.chars();
+  match chars.next() {
+    Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+    None => String::new(),
+  }
+}
+
+/// `user-card` becomes `UserCard`.
+fn pascal_case(name: &str) -> String {
+  words(name).iter().map(|word| capitalize(word)).collect()
+}
+
+/// `shopping-cart` becomes `shoppingCart`.
+fn camel_case(name: &str) -> String {
+  let pascal = pascal_case(name);
+  let mut chars = pascal.chars();
+  match chars.next() {
+    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+    None => String::new(),
+  }
+}
+
+/// `UserCard` becomes `user-card`.
+fn kebab_case(name: &str) -> String {
+  words(name).join("-")
+}
+
+#[test]
+fn test_cases() {
+  assert_eq!(pascal_case("user-card"), "UserCard");
+  assert_eq!(pascal_case("UserCard"), "UserCard");
+  assert_eq!(camel_case("shopping_cart"), "shoppingCart");
+  assert_eq!(kebab_case("UserCard"), "user-card");
+  assert!(is_valid_name("UserCard"));
+  assert!(!is_valid_name("1card"));
+  assert!(!is_valid_name("user card"));
+}
+
+#[test]
+fn test_generate() {
+  use crate::{
+    scaffold::{scaffold_into, ScaffoldOptions},
+    vfs::MemoryFs,
+  };
+
+  let root = Path::new("/vue-project");
+  let mut fs = MemoryFs::new();
+  scaffold_into(&ScaffoldOptions::default(), root, &mut fs).unwrap();
+  assert!(matches!(
+    generate(root, Generator::View, "Settings", &mut fs),
+    Err(Error::MissingFeature(_))
+  ));
+
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    needs_router: true,
+    needs_pinia: true,
+    ..Default::default()
+  };
+  let mut fs = MemoryFs::new();
+  scaffold_into(&options, root, &mut fs).unwrap();
+
+  let report = generate(root, Generator::Component, "user-card", &mut fs).unwrap();
+  assert_eq!(report.created, [root.join("src/components/UserCard.vue")]);
+  let component = fs.read_to_string(&report.created[0]).unwrap().unwrap();
+  assert!(component.starts_with("<script setup lang=\"ts\">\ndefineProps<{"));
+  assert!(component.contains("<div class=\"user-card\">\n    <h1>{{ msg }}</h1>"));
+
+  let report = generate(root, Generator::Component, "UserCard", &mut fs).unwrap();
+  assert_eq!(report.conflicts, [root.join("src/components/UserCard.vue")]);
+
+  let report = generate(root, Generator::View, "UserSettings", &mut fs).unwrap();
+  assert_eq!(report.created, [root.join("src/views/UserSettingsView.vue")]);
+  assert_eq!(report.updated, [root.join("src/router/index.ts")]);
+  let view = fs.read_to_string(&report.created[0]).unwrap().unwrap();
+  assert!(view.contains("<h1>This is the user-settings page</h1>"));
+  let router = fs.read_to_string(&report.updated[0]).unwrap().unwrap();
+  assert!(router.contains(
+    "      component: () => import('../views/AboutView.vue')
+    },
+    {
+      path: '/user-settings',
+      name: 'user-settings',
+      component: () => import('../views/UserSettingsView.vue')
+    }
+  ]"
+  ));
+
+  let report = generate(root, Generator::Store, "shopping-cart", &mut fs).unwrap();
+  assert_eq!(report.created, [root.join("src/stores/shoppingCart.ts")]);
+  let store = fs.read_to_string(&report.created[0]).unwrap().unwrap();
+  assert!(store.contains("export const useShoppingCartStore = defineStore('shoppingCart', () => {"));
+}
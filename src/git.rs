@@ -0,0 +1,98 @@
+use std::{
+  io,
+  path::Path,
+  process::{Command, Output},
+};
+
+/// What [`init_repository`] did.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GitInit {
+  /// A new repository with an initial commit.
+  Committed,
+  /// The project already was a repository, which is left as it is.
+  Existing,
+  /// The project is inside another repository, so no repository of its own is created.
+  InsideRepository,
+  /// A new repository without a commit, because git has no author configured.
+  NoAuthor,
+}
+
+/// Runs `git init`, `git add -A` and `git commit` in `root`, authored by the user from the git
+/// config. Errors are meant to be shown as warnings, the project itself is complete by now.
+pub fn init_repository(root: &Path, message: &str) -> Result<GitInit, String> {
+  match git(root, &["--version"]) {
+    Err(err) if err.kind() == io::ErrorKind::NotFound => {
+      return Err("git is not installed, skipping the repository setup".to_string())
+    }
+    result => check(result)?,
+  };
+
+  if root.join(".git").exists() {
+    return Ok(GitInit::Existing);
+  }
+  // e.g. a new package in a monorepo, which belongs to the repository around it
+  if git(root, &["rev-parse", "--is-inside-work-tree"])
+    .is_ok_and(|output| output.status.success() && output.stdout.trim_ascii() == b"true")
+  {
+    return Ok(GitInit::InsideRepository);
+  }
+
+  check(git(root, &["init", "--quiet"]))?;
+  check(git(root, &["add", "--all"]))?;
+
+  // `git var` honors the config as well as GIT_AUTHOR_NAME and friends
+  if check(git(root, &["var", "GIT_AUTHOR_IDENT"])).is_err() {
+    return Ok(GitInit::NoAuthor);
+  }
+  check(git(root, &["commit", "--quiet", "--message", message]))?;
+
+  Ok(GitInit::Committed)
+}
+
+fn git(root: &Path, args: &[&str]) -> io::Result<Output> {
+  Command::new("git").args(args).current_dir(root).output()
+}
+
+fn check(result: io::Result<Output>) -> Result<(), String> {
+  let output = result.map_err(|err| format!("failed to run git: {}", err))?;
+  if output.status.success() {
+    Ok(())
+  } else {
+    Err(format!(
+      "git failed: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    ))
+  }
+}
+
+#[test]
+fn test_init_repository() {
+  use std::fs;
+
+  let root = std::env::temp_dir().join("create-vue-rust-git");
+  let _ = fs::remove_dir_all(&root);
+  fs::create_dir_all(&root).unwrap();
+  fs::write(root.join("package.json"), "{}").unwrap();
+
+  let result = init_repository(&root, "Initial commit");
+  if git(&root, &["--version"]).is_ok() {
+    assert!(matches!(result, Ok(GitInit::Committed | GitInit::NoAuthor)));
+    assert!(root.join(".git").is_dir());
+    assert_eq!(
+      init_repository(&root, "Initial commit"),
+      Ok(GitInit::Existing)
+    );
+
+    let nested = root.join("packages/app");
+    fs::create_dir_all(&nested).unwrap();
+    assert_eq!(
+      init_repository(&nested, "Initial commit"),
+      Ok(GitInit::InsideRepository)
+    );
+    assert!(!nested.join(".git").exists());
+  } else {
+    assert!(result.is_err());
+  }
+
+  fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,151 @@
+use std::{
+  collections::VecDeque,
+  io::{BufRead, BufReader, Read},
+  path::Path,
+  process::{Command, Stdio},
+  sync::mpsc,
+  thread,
+  time::Duration,
+};
+
+use colorful::Colorful;
+use create_vue_rust::{Error, PackageManager, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Replaces the install command, e.g. `npm install --registry http://localhost:4873`.
+pub const INSTALL_COMMAND_ENV: &str = "CREATE_VUE_INSTALL_COMMAND";
+
+/// How many lines of output are repeated when the install fails.
+const SUMMARY_LINES: usize = 15;
+
+/// The install command from [`INSTALL_COMMAND_ENV`] if it is set, or the package manager's.
+pub fn install_command(
+  package_manager: PackageManager,
+  env_override: Option<String>,
+) -> Result<Vec<String>> {
+  let command = env_override
+    .filter(|command| !command.trim().is_empty())
+    .unwrap_or_else(|| package_manager.install());
+  let words = shell_words::split(&command)
+    .map_err(|err| Error::Install(format!("{} ({}: {})", command, INSTALL_COMMAND_ENV, err)))?;
+  Ok(words)
+}
+
+/// Runs `command` in `root`, streaming its output below a spinner. On failure the last lines
+/// of output are repeated, so the cause is not lost in a long log.
+pub fn install(root: &Path, command: &[String]) -> Result<()> {
+  let display = shell_words::join(command);
+  let (program, args) = command
+    .split_first()
+    .ok_or_else(|| Error::Install(display.clone()))?;
+
+  let mut child = match Command::new(program)
+    .args(args)
+    .current_dir(root)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+  {
+    Ok(child) => child,
+    Err(err) => {
+      eprintln!("{} {}: {}", "✖".red(), program, err);
+      return Err(Error::Install(display));
+    }
+  };
+
+  let (sender, receiver) = mpsc::channel();
+  let stdout = child.stdout.take().map(|out| forward(out, sender.clone()));
+  let stderr = child.stderr.take().map(|err| forward(err, sender));
+
+  let spinner = ProgressBar::new_spinner();
+  spinner.set_style(ProgressStyle::with_template("{spinner:.green} {msg}").unwrap());
+  spinner.set_message(format!("Running {}", display));
+
+  let mut last_lines = VecDeque::with_capacity(SUMMARY_LINES);
+  loop {
+    match receiver.recv_timeout(Duration::from_millis(100)) {
+      Ok(line) => {
+        // printed even when the spinner is hidden because stderr is not a terminal
+        spinner.suspend(|| println!("  {}", line.as_str().dim()));
+        if last_lines.len() == SUMMARY_LINES {
+          last_lines.pop_front();
+        }
+        last_lines.push_back(line);
+      }
+      Err(mpsc::RecvTimeoutError::Timeout) => spinner.tick(),
+      Err(mpsc::RecvTimeoutError::Disconnected) => break,
+    }
+  }
+  for handle in stdout.into_iter().chain(stderr) {
+    let _ = handle.join();
+  }
+  let status = child.wait()?;
+  spinner.finish_and_clear();
+
+  if status.success() {
+    return Ok(());
+  }
+
+  eprintln!("\n{} {} exited with {}", "✖".red(), display, status);
+  if !last_lines.is_empty() {
+    eprintln!("Last lines of its output:");
+    for line in &last_lines {
+      eprintln!("  {}", line);
+    }
+  }
+  Err(Error::Install(display))
+}
+
+fn forward(
+  output: impl Read + Send + 'static,
+  sender: mpsc::Sender<String>,
+) -> thread::JoinHandle<()> {
+  thread::spawn(move || {
+    for line in BufReader::new(output).lines().map_while(|line| line.ok()) {
+      if sender.send(line).is_err() {
+        break;
+      }
+    }
+  })
+}
+
+#[test]
+fn test_install_command() {
+  assert_eq!(
+    install_command(PackageManager::Yarn, None).unwrap(),
+    ["yarn"]
+  );
+  assert_eq!(
+    install_command(PackageManager::Npm, Some(" ".to_string())).unwrap(),
+    ["npm", "install"]
+  );
+  assert_eq!(
+    install_command(
+      PackageManager::Npm,
+      Some("npm install --registry 'http://localhost:4873'".to_string())
+    )
+    .unwrap(),
+    ["npm", "install", "--registry", "http://localhost:4873"]
+  );
+  assert!(matches!(
+    install_command(PackageManager::Npm, Some("npm 'install".to_string())),
+    Err(Error::Install(_))
+  ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_install() {
+  let root = std::env::temp_dir();
+  let command = |script: &str| ["sh", "-c", script].map(str::to_string);
+  assert!(install(&root, &command("echo installed; echo warning >&2")).is_ok());
+  assert!(matches!(
+    install(&root, &command("echo failed >&2; exit 3")),
+    Err(Error::Install(_))
+  ));
+  assert!(matches!(
+    install(&root, &["create-vue-rust-missing-binary".to_string()]),
+    Err(Error::Install(_))
+  ));
+}
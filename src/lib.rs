@@ -0,0 +1,30 @@
+mod add;
+mod backup;
+mod conflicts;
+mod convert_typescript;
+mod create_eslint_config;
+mod editor_config;
+mod error;
+mod generate;
+pub mod manifest;
+mod package_manager;
+mod placeholders;
+mod readme;
+mod render;
+mod render_eslint;
+mod scaffold;
+pub mod snapshots;
+pub mod vfs;
+mod templates;
+mod transaction;
+pub mod utils;
+
+pub use add::{add_feature, detect_options, ChangeReport, Feature};
+pub use backup::backup_dir;
+pub use conflicts::{plan_scaffold, Conflict, MergePlan, Resolution};
+pub use create_eslint_config::StyleGuide;
+pub use error::{Error, Result};
+pub use generate::{generate, Generator};
+pub use package_manager::PackageManager;
+pub use scaffold::{scaffold, scaffold_cancellable, scaffold_into, E2eTesting, ScaffoldOptions};
+pub use templates::Layer;
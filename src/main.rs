@@ -1,140 +1,244 @@
 mod args;
-mod prompts;
-mod render;
-mod utils;
 mod banner;
-mod editor_config;
-mod create_eslint_config;
-mod render_eslint;
+mod git;
+mod install;
+mod prompts;
 
+use std::{
+  env,
+  path::Path,
+  process,
+  sync::atomic::{AtomicBool, Ordering},
+  time::SystemTime,
+};
 
 use args::Args;
 use clap::Parser;
-use serde_json::json;
-use std::{env, fs};
+use colorful::Colorful;
+use create_vue_rust::{
+  add_feature, backup_dir, generate, plan_scaffold, scaffold_cancellable, scaffold_into,
+  snapshots::verify_templates,
+  vfs::{DiskFs, FileSystem, MemoryFs},
+  utils::get_path_from_cwd,
+  ChangeReport, Error, Feature, Generator, Result,
+};
 
 use crate::{
-  prompts::{E2eTesting, Prompts},
-  render::render_template,
-  utils::{empty_dir, get_path_from_cwd, mkdir}, banner::print_banner,
+  args::Command,
+  banner::print_banner,
+  git::{init_repository, GitInit},
+  install::{install, install_command, INSTALL_COMMAND_ENV},
+  prompts::{resolve_conflict, ConflictMode, Prompts},
 };
 
+/// Set while the project is being written, so Ctrl-C rolls it back instead of exiting.
+static SCAFFOLDING: AtomicBool = AtomicBool::new(false);
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
 fn main() {
   print_banner();
 
-  let args = Args::parse();
+  let _ = ctrlc::set_handler(|| {
+    if SCAFFOLDING.load(Ordering::SeqCst) {
+      CANCELLED.store(true, Ordering::SeqCst);
+    } else {
+      let _ = console::Term::stderr().show_cursor();
+      process::exit(Error::Cancelled.exit_code());
+    }
+  });
 
-  let prompts = Prompts::new(&args);
+  let args = Args::parse();
 
-  let Prompts {
-    package_name,
-    project_name,
-    needs_e2e_testing,
-    needs_eslint,
-    needs_jsx,
-    needs_pinia,
-    needs_prettier,
-    needs_router,
-    needs_type_script,
-    needs_vitest,
-    should_overwrite,
-  } = prompts;
-
-  let needs_cypress = match needs_e2e_testing {
-    E2eTesting::Cypress => true,
-    _ => false,
-  };
-
-  let needs_cypress_ct = needs_cypress && !needs_vitest;
-
-  let needs_play_wright = match needs_e2e_testing {
-    E2eTesting::Playwright => true,
-    _ => false,
-  };
-
-  if should_overwrite {
-    empty_dir(&project_name).unwrap();
-  } else {
-    mkdir(&project_name).unwrap();
+  if let Err(err) = run(&args) {
+    eprintln!("\n{} {}", "✖".red(), err.to_string().red());
+    process::exit(err.exit_code());
   }
+}
 
-  let project_root = get_path_from_cwd(&project_name).unwrap();
-
-  println!("\nScaffolding project in: {:?}", &project_root);
+fn run(args: &Args) -> Result<()> {
+  match &args.command {
+    Some(Command::Add { feature, dir }) => run_add(*feature, dir),
+    Some(Command::Generate {
+      generator,
+      name,
+      dir,
+    }) => run_generate(*generator, name, dir),
+    Some(Command::VerifyTemplates { dir, update }) => run_verify_templates(dir, *update),
+    None => run_create(args),
+  }
+}
 
-  let pkg = json!({
-    "name":package_name.clone(),
-    "version":"0.0.0",
-  });
+fn run_add(feature: Feature, dir: &Path) -> Result<()> {
+  let root = std::path::absolute(dir)?;
+  let report = add_feature(&root, feature, &mut DiskFs)?;
 
-  let pkg_str = serde_json::to_string_pretty(&pkg).unwrap();
+  println!("\nAdded {} to {:?}", feature, root);
+  print_changes(&root, &report);
+  Ok(())
+}
 
-  fs::write(&project_root.join("package.json"), &pkg_str).unwrap();
+fn run_generate(generator: Generator, name: &str, dir: &Path) -> Result<()> {
+  let root = std::path::absolute(dir)?;
+  let report = generate(&root, generator, name, &mut DiskFs)?;
 
-  render(vec!["base"], &project_name);
+  println!("\nGenerated {} {} in {:?}", generator, name, root);
+  print_changes(&root, &report);
+  Ok(())
+}
 
-  if needs_jsx {
-    render(vec!["config", "jsx"], &project_name);
+fn print_changes(root: &Path, report: &ChangeReport) {
+  let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).display().to_string();
+  for path in &report.created {
+    println!("  {} {}", "created".green(), relative(path));
   }
-
-  if needs_router {
-    render(vec!["config", "router"], &project_name);
+  for path in &report.updated {
+    println!("  {} {}", "updated".green(), relative(path));
   }
-
-  if needs_pinia {
-    render(vec!["config", "pinia"], &project_name);
+  if !report.conflicts.is_empty() {
+    println!(
+      "\n{}",
+      "These files already exist and were left untouched, update them by hand:".yellow()
+    );
+    for path in &report.conflicts {
+      println!("  {} {}", "skipped".yellow(), relative(path));
+    }
   }
+}
 
-  if needs_vitest {
-    render(vec!["config", "vitest"], &project_name);
+fn run_verify_templates(dir: &Path, update: bool) -> Result<()> {
+  let drifts = verify_templates(dir, update, &mut DiskFs)?;
+  if update {
+    println!("Updated the snapshots in {:?}", dir);
+    return Ok(());
   }
 
-  if needs_cypress {
-    render(vec!["config, cypress"], &project_name);
+  for drift in &drifts {
+    println!("{}", drift);
   }
-
-  if needs_cypress_ct {
-    render(vec!["config", "cypress-ct"], &project_name);
+  if !drifts.is_empty() {
+    return Err(Error::SnapshotDrift(drifts.len()));
   }
 
-  if needs_play_wright {
-    render(vec!["config", "playwright"], &project_name);
-  }
+  println!("All feature combinations match the snapshots in {:?}", dir);
+  Ok(())
+}
 
-  // render typescript configs
-  if needs_type_script {
-    render(vec!["config", "typescript"], &project_name);
-    render(vec!["tsconfig", "base"], &project_name);
-    if needs_cypress {
-      render(vec!["tsconfig", "cypress"], &project_name);
-    }
+fn run_create(args: &Args) -> Result<()> {
+  let Prompts {
+    project_name,
+    existing_files,
+    options,
+    needs_git,
+  } = Prompts::new(args)?;
 
-    if needs_cypress_ct {
-      render(vec!["tsconfig", "cypress-ct"], &project_name);
-    }
+  let project_root = get_path_from_cwd(&project_name)?;
+
+  if args.dry_run {
+    let mut fs = MemoryFs::new();
+    scaffold_into(&options, &project_root, &mut fs)?;
 
-    if needs_play_wright {
-      render(vec!["tsconfig", "playwright"], &project_name);
+    println!("\nDry run, nothing was written to {:?}\n", &project_root);
+    println!("{}/", project_name);
+    for line in fs.tree(&project_root).lines() {
+      println!("  {}", line);
     }
 
-    if needs_vitest {
-      render(vec!["tsconfig", "vitest"], &project_name);
+    let pkg = fs.read_to_string(&project_root.join("package.json"))?;
+    println!("\npackage.json:\n{}", pkg.unwrap_or_default());
+    return Ok(());
+  }
+
+  println!("\nScaffolding project in: {:?}", &project_root);
+
+  match existing_files {
+    None | Some(ConflictMode::Replace) => {
+      if existing_files.is_some() {
+        if !args.no_backup {
+          let backup_to = match &args.backup_dir {
+            Some(dir) => std::path::absolute(dir)?,
+            None => project_root.parent().unwrap_or(&project_root).to_path_buf(),
+          };
+          if let Some(archive) =
+            backup_dir(&mut DiskFs, &project_root, &backup_to, SystemTime::now())?
+          {
+            println!("Backed up the existing files to {:?}", archive);
+          }
+        }
+        // the old files are kept until the new project is complete
+        println!("Existing files will be replaced, except .git");
+      }
+
+      SCAFFOLDING.store(true, Ordering::SeqCst);
+      let result = scaffold_cancellable(
+        &options,
+        &project_root,
+        existing_files.is_some(),
+        &CANCELLED,
+      );
+      SCAFFOLDING.store(false, Ordering::SeqCst);
+      result?;
+    }
+    Some(mode) => {
+      let plan = plan_scaffold(&options, &project_root, &DiskFs, |conflict| {
+        resolve_conflict(mode, args.merge_json, &project_root, conflict)
+      })?;
+
+      // defer Ctrl-C until the planned files are written
+      SCAFFOLDING.store(true, Ordering::SeqCst);
+      let result = plan.apply(&mut DiskFs);
+      SCAFFOLDING.store(false, Ordering::SeqCst);
+      print_changes(&project_root, &result?);
     }
   }
 
-  println!("Hello, world!,{:?}", args);
-}
+  // a Ctrl-C deferred while the files were written still stops before git and the install
+  if CANCELLED.load(Ordering::SeqCst) {
+    return Err(Error::Cancelled);
+  }
 
-fn render(names: Vec<&str>, project_name: &String) -> () {
-  let mut template_path = env::current_dir().unwrap();
-  template_path.push("src");
-  template_path.push("template");
+  if needs_git {
+    match init_repository(&project_root, &args.commit_message) {
+      Ok(GitInit::Committed) => println!("Initialized a git repository with an initial commit"),
+      Ok(GitInit::Existing) => println!("Kept the existing git repository"),
+      Ok(GitInit::InsideRepository) => {
+        println!("Skipped git init, the project is inside an existing git repository")
+      }
+      Ok(GitInit::NoAuthor) => println!(
+        "{}",
+        "Initialized a git repository, set git's user.name and user.email to commit".yellow()
+      ),
+      Err(warning) => println!("{} {}", "!".yellow(), warning.yellow()),
+    }
+  }
 
-  for name in names {
-    template_path.push(name);
+  let package_manager = options.package_manager;
+  if args.install {
+    let command = install_command(package_manager, env::var(INSTALL_COMMAND_ENV).ok())?;
+    println!("\nInstalling dependencies with {}", shell_words::join(&command));
+    install(&project_root, &command)?;
   }
 
-  let dest = get_path_from_cwd(project_name).unwrap();
+  let pkg = DiskFs
+    .read_to_string(&project_root.join("package.json"))?
+    .unwrap_or_default();
+  let pkg = serde_json::from_str(&pkg).unwrap_or_default();
+
+  println!("\nDone. Now run:\n");
+  if project_root != env::current_dir()? {
+    let cd = if project_name.contains(char::is_whitespace) {
+      format!("\"{}\"", project_name)
+    } else {
+      project_name
+    };
+    println!("  {}", format!("cd {}", cd).bold().green());
+  }
+  for step in package_manager.next_steps(&pkg) {
+    if args.install && step == package_manager.install() {
+      continue;
+    }
+    println!("  {}", step.bold().green());
+  }
+  println!();
 
-  render_template(&template_path, &dest).unwrap()
+  Ok(())
 }
@@ -0,0 +1,223 @@
+use serde::Deserialize;
+
+use crate::{
+  error::{Error, Result},
+  scaffold::{E2eTesting, ScaffoldOptions},
+  templates::get_template_file,
+};
+
+/// The file name of a manifest, in the built-in template root or a custom template directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The layers of a template and the features that activate them, rendered in order.
+///
+/// A condition is a boolean expression over the feature flags, e.g. `typescript && !vitest`,
+/// with `!`, `&&`, `||` and parentheses. A layer without a condition is always rendered.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+  pub layers: Vec<Layer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Layer {
+  /// The layer directory, relative to the template root.
+  pub path: String,
+  pub when: Option<String>,
+}
+
+impl Manifest {
+  pub fn parse(json: &str) -> Result<Self> {
+    serde_json::from_str(json).map_err(|err| Error::InvalidManifest(err.to_string()))
+  }
+
+  /// The manifest of the built-in templates.
+  pub fn builtin() -> Result<Self> {
+    let json = get_template_file(MANIFEST_FILE)?;
+    Self::parse(&String::from_utf8_lossy(json))
+  }
+
+  /// The paths of the layers to render for `options`, in order.
+  pub fn resolve(&self, options: &ScaffoldOptions) -> Result<Vec<&str>> {
+    let mut paths = vec![];
+    for layer in &self.layers {
+      let active = match &layer.when {
+        Some(condition) => evaluate(condition, options)?,
+        None => true,
+      };
+      if active {
+        paths.push(layer.path.as_str());
+      }
+    }
+    Ok(paths)
+  }
+}
+
+/// The feature flags a condition can refer to.
+pub fn feature(name: &str, options: &ScaffoldOptions) -> Option<bool> {
+  let value = match name {
+    "typescript" => options.needs_type_script,
+    "jsx" => options.needs_jsx,
+    "router" => options.needs_router,
+    "pinia" => options.needs_pinia,
+    "vitest" => options.needs_vitest,
+    "cypress" => matches!(options.needs_e2e_testing, E2eTesting::Cypress),
+    "playwright" => matches!(options.needs_e2e_testing, E2eTesting::Playwright),
+    "eslint" => options.needs_eslint,
+    "prettier" => options.needs_prettier,
+    "true" => true,
+    "false" => false,
+    _ => return None,
+  };
+  Some(value)
+}
+
+/// Evaluates a layer condition against `options`.
+pub fn evaluate(condition: &str, options: &ScaffoldOptions) -> Result<bool> {
+  let invalid = |message: String| {
+    Error::InvalidManifest(format!("condition {:?}: {}", condition, message))
+  };
+
+  let tokens = tokenize(condition).map_err(invalid)?;
+  let mut parser = Parser {
+    tokens: &tokens,
+    position: 0,
+    options,
+  };
+  let value = parser.or().map_err(invalid)?;
+  match parser.next() {
+    None => Ok(value),
+    Some(token) => Err(invalid(format!("unexpected `{}`", token))),
+  }
+}
+
+fn tokenize(condition: &str) -> std::result::Result<Vec<String>, String> {
+  let is_name = |char: &char| char.is_ascii_alphanumeric() || *char == '-' || *char == '_';
+
+  let mut tokens = vec![];
+  let mut chars = condition.chars().peekable();
+  while let Some(char) = chars.next() {
+    match char {
+      ' ' | '\t' => {}
+      '!' | '(' | ')' => tokens.push(char.to_string()),
+      '&' | '|' => {
+        if chars.next() != Some(char) {
+          return Err(format!("expected `{}{}`", char, char));
+        }
+        tokens.push(format!("{}{}", char, char));
+      }
+      _ if is_name(&char) => {
+        let mut name = char.to_string();
+        while let Some(next) = chars.next_if(is_name) {
+          name.push(next);
+        }
+        tokens.push(name);
+      }
+      _ => return Err(format!("unexpected `{}`", char)),
+    }
+  }
+  Ok(tokens)
+}
+
+struct Parser<'a> {
+  tokens: &'a [String],
+  position: usize,
+  options: &'a ScaffoldOptions,
+}
+
+impl Parser<'_> {
+  fn next(&mut self) -> Option<&str> {
+    let token = self.tokens.get(self.position)?;
+    self.position += 1;
+    Some(token)
+  }
+
+  fn peek_is(&self, expected: &str) -> bool {
+    self.tokens.get(self.position).is_some_and(|token| token == expected)
+  }
+
+  fn or(&mut self) -> std::result::Result<bool, String> {
+    let mut value = self.and()?;
+    while self.peek_is("||") {
+      self.position += 1;
+      value |= self.and()?;
+    }
+    Ok(value)
+  }
+
+  fn and(&mut self) -> std::result::Result<bool, String> {
+    let mut value = self.unary()?;
+    while self.peek_is("&&") {
+      self.position += 1;
+      value &= self.unary()?;
+    }
+    Ok(value)
+  }
+
+  fn unary(&mut self) -> std::result::Result<bool, String> {
+    let options = self.options;
+    match self.next() {
+      Some("!") => Ok(!self.unary()?),
+      Some("(") => {
+        let value = self.or()?;
+        if self.next() != Some(")") {
+          return Err("expected `)`".to_string());
+        }
+        Ok(value)
+      }
+      Some(name) => feature(name, options).ok_or_else(|| format!("unknown feature `{}`", name)),
+      None => Err("unexpected end".to_string()),
+    }
+  }
+}
+
+#[test]
+fn test_evaluate() {
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    needs_e2e_testing: E2eTesting::Cypress,
+    ..Default::default()
+  };
+  assert!(evaluate("typescript", &options).unwrap());
+  assert!(evaluate("cypress && !vitest", &options).unwrap());
+  assert!(!evaluate("typescript && (vitest || playwright)", &options).unwrap());
+  assert!(evaluate("!(jsx || router) && true", &options).unwrap());
+
+  for condition in ["typescirpt", "typescript &", "(router", "router router", ""] {
+    assert!(
+      matches!(evaluate(condition, &options), Err(Error::InvalidManifest(_))),
+      "{:?}",
+      condition
+    );
+  }
+}
+
+#[test]
+fn test_builtin_manifest() {
+  let manifest = Manifest::builtin().unwrap();
+  let layers = |options: &ScaffoldOptions| manifest.resolve(options).unwrap().join(" ");
+
+  assert_eq!(
+    layers(&ScaffoldOptions::default()),
+    "base code/default entry/default"
+  );
+  assert_eq!(
+    layers(&ScaffoldOptions {
+      needs_type_script: true,
+      needs_router: true,
+      needs_pinia: true,
+      needs_e2e_testing: E2eTesting::Cypress,
+      ..Default::default()
+    }),
+    "base config/router config/pinia config/cypress config/cypress-ct config/typescript \
+     tsconfig/base tsconfig/cypress tsconfig/cypress-ct code/typescript-router \
+     entry/router-and-pinia"
+  );
+  assert_eq!(
+    layers(&ScaffoldOptions {
+      needs_vitest: true,
+      needs_e2e_testing: E2eTesting::Cypress,
+      ..Default::default()
+    }),
+    "base config/vitest config/cypress code/default entry/default"
+  );
+}
@@ -0,0 +1,151 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PackageManager {
+  #[default]
+  Npm,
+  Pnpm,
+  Yarn,
+  Bun,
+}
+
+impl Display for PackageManager {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PackageManager::Npm => write!(f, "npm"),
+      PackageManager::Pnpm => write!(f, "pnpm"),
+      PackageManager::Yarn => write!(f, "yarn"),
+      PackageManager::Bun => write!(f, "bun"),
+    }
+  }
+}
+
+impl PackageManager {
+  /// Detects the package manager from the `npm_config_user_agent` every package manager sets
+  /// for the scripts it runs, e.g. `pnpm/7.18.0 npm/? node/v18.12.1 linux x64`.
+  pub fn from_user_agent(user_agent: &str) -> Option<Self> {
+    let name = user_agent.split('/').next()?;
+    match name {
+      "npm" => Some(PackageManager::Npm),
+      "pnpm" => Some(PackageManager::Pnpm),
+      "yarn" => Some(PackageManager::Yarn),
+      "bun" => Some(PackageManager::Bun),
+      _ => None,
+    }
+  }
+
+  /// The package manager that runs this process, or npm.
+  pub fn detect() -> Self {
+    std::env::var("npm_config_user_agent")
+      .ok()
+      .and_then(|user_agent| Self::from_user_agent(&user_agent))
+      .unwrap_or_default()
+  }
+
+  pub fn install(self) -> String {
+    match self {
+      PackageManager::Yarn => "yarn".to_string(),
+      _ => format!("{} install", self),
+    }
+  }
+
+  /// npm needs `run` for every script, and bun for the ones that clash with its own commands,
+  /// like `build` and `test`.
+  pub fn run(self, script: &str) -> String {
+    match self {
+      PackageManager::Npm | PackageManager::Bun => format!("{} run {}", self, script),
+      _ => format!("{} {}", self, script),
+    }
+  }
+
+  /// Runs `script` with extra arguments, which npm only passes on after a `--`.
+  pub fn run_with_args(self, script: &str, args: &str) -> String {
+    match self {
+      PackageManager::Npm => format!("npm run {} -- {}", script, args),
+      _ => format!("{} {}", self.run(script), args),
+    }
+  }
+
+  /// Runs a binary of an installed package, like `npx`.
+  pub fn exec(self, command: &str) -> String {
+    match self {
+      PackageManager::Npm => format!("npx {}", command),
+      PackageManager::Pnpm => format!("pnpm exec {}", command),
+      PackageManager::Yarn => format!("yarn {}", command),
+      PackageManager::Bun => format!("bunx {}", command),
+    }
+  }
+
+  /// The commands to get started with a project whose `package.json` is `pkg`: installing,
+  /// then the lint and test scripts it actually has, then the dev server.
+  pub fn next_steps(self, pkg: &Value) -> Vec<String> {
+    let scripts = pkg["scripts"].as_object();
+    let has_script = |name: &str| scripts.is_some_and(|scripts| scripts.contains_key(name));
+
+    let mut steps = vec![self.install()];
+    for script in ["lint", "test:unit", "test:e2e"] {
+      if has_script(script) {
+        steps.push(self.run(script));
+      }
+    }
+    if has_script("dev") {
+      steps.push(self.run("dev"));
+    }
+    steps
+  }
+}
+
+#[test]
+fn test_from_user_agent() {
+  let detect = PackageManager::from_user_agent;
+  assert_eq!(
+    detect("npm/8.19.2 node/v18.12.1 linux x64 workspaces/false"),
+    Some(PackageManager::Npm)
+  );
+  assert_eq!(
+    detect("pnpm/7.18.0 npm/? node/v18.12.1 linux x64"),
+    Some(PackageManager::Pnpm)
+  );
+  assert_eq!(
+    detect("yarn/1.22.19 npm/? node/v18.12.1 linux x64"),
+    Some(PackageManager::Yarn)
+  );
+  assert_eq!(
+    detect("bun/0.3.0 npm/? node/v18.12.1 linux x64"),
+    Some(PackageManager::Bun)
+  );
+  assert_eq!(detect("cnpm/9.0.0"), None);
+  assert_eq!(detect(""), None);
+}
+
+#[test]
+fn test_next_steps() {
+  let pkg = serde_json::json!({
+    "scripts": {
+      "dev": "vite",
+      "build": "vite build",
+      "test:unit": "vitest",
+      "lint": "eslint ."
+    }
+  });
+  assert_eq!(
+    PackageManager::Npm.next_steps(&pkg),
+    [
+      "npm install",
+      "npm run lint",
+      "npm run test:unit",
+      "npm run dev"
+    ]
+  );
+  assert_eq!(
+    PackageManager::Yarn.next_steps(&pkg),
+    ["yarn", "yarn lint", "yarn test:unit", "yarn dev"]
+  );
+  assert_eq!(
+    PackageManager::Pnpm.next_steps(&serde_json::json!({})),
+    ["pnpm install"]
+  );
+}
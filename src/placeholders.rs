@@ -0,0 +1,202 @@
+use crate::{
+  error::Error,
+  manifest::{evaluate, feature},
+  scaffold::ScaffoldOptions,
+};
+
+const OPEN: &str = "<%";
+const CLOSE: &str = "%>";
+
+/// Renders the placeholders of a template text file against `options`.
+///
+/// `<%= name %>` inserts `projectName`, `packageName` or one of the feature flags of the
+/// manifest conditions, e.g. `<%= typescript %>`. Lines holding nothing but
+/// `<% if condition %>`, `<% else %>` or `<% endif %>` are removed, and keep the lines between
+/// them only when the condition holds. Files without `<%` are returned as they are.
+///
+/// `<%%` writes a literal `<%`. Unknown variables are left as they are, so a custom template
+/// can keep the `<%= BASE_URL %>` of another tool. With `html`, the inserted values are
+/// escaped, e.g. for a project named `Tom & Jerry`.
+pub fn substitute(
+  contents: &str,
+  options: &ScaffoldOptions,
+  html: bool,
+) -> std::result::Result<String, String> {
+  if !contents.contains(OPEN) {
+    return Ok(contents.to_string());
+  }
+
+  // whether the lines of each open block, and of all blocks around it, are kept
+  let mut blocks: Vec<(bool, bool)> = vec![];
+  let mut output = String::with_capacity(contents.len());
+
+  for (index, line) in contents.split_inclusive('\n').enumerate() {
+    let error = |message: String| format!("line {}: {}", index + 1, message);
+    let kept = blocks.last().is_none_or(|&(_, kept)| kept);
+
+    match directive(line) {
+      Some(("if", condition)) => {
+        let value = condition_value(condition, options).map_err(error)?;
+        blocks.push((kept, kept && value));
+      }
+      Some(("else", "")) => {
+        let (outer, value) = blocks
+          .pop()
+          .ok_or_else(|| error("`else` without `if`".to_string()))?;
+        blocks.push((outer, outer && !value));
+      }
+      Some(("endif", "")) => {
+        blocks
+          .pop()
+          .ok_or_else(|| error("`endif` without `if`".to_string()))?;
+      }
+      Some((keyword, _)) => return Err(error(format!("unknown directive `{}`", keyword))),
+      None if kept => output.push_str(&interpolate(line, options, html).map_err(error)?),
+      None => {}
+    }
+  }
+
+  if !blocks.is_empty() {
+    return Err("`if` without `endif`".to_string());
+  }
+  Ok(output)
+}
+
+/// Splits a line like `<% if router %>` into its keyword and argument.
+fn directive(line: &str) -> Option<(&str, &str)> {
+  let inner = line.trim().strip_prefix(OPEN)?.strip_suffix(CLOSE)?;
+  if inner.starts_with('=') || inner.starts_with('%') {
+    return None;
+  }
+  let inner = inner.trim();
+  Some(
+    inner
+      .split_once(' ')
+      .map_or((inner, ""), |(keyword, rest)| (keyword, rest.trim())),
+  )
+}
+
+fn condition_value(
+  condition: &str,
+  options: &ScaffoldOptions,
+) -> std::result::Result<bool, String> {
+  evaluate(condition, options).map_err(|err| match err {
+    Error::InvalidManifest(message) => message,
+    err => err.to_string(),
+  })
+}
+
+fn interpolate(
+  line: &str,
+  options: &ScaffoldOptions,
+  html: bool,
+) -> std::result::Result<String, String> {
+  let mut output = String::with_capacity(line.len());
+  let mut rest = line;
+  while let Some(start) = rest.find(OPEN) {
+    output.push_str(&rest[..start]);
+    let tag = &rest[start..];
+    if !tag.starts_with("<%=") {
+      // `<%%` is the escaped `<%`, any other `<%` is not a variable
+      output.push_str(OPEN);
+      rest = tag[OPEN.len()..].strip_prefix('%').unwrap_or(&tag[OPEN.len()..]);
+      continue;
+    }
+    let end = tag.find(CLOSE).ok_or_else(|| "unclosed `<%=`".to_string())?;
+    match value(tag[3..end].trim(), options) {
+      Some(value) if html => output.push_str(&escape_html(&value)),
+      Some(value) => output.push_str(&value),
+      None => output.push_str(&tag[..end + CLOSE.len()]),
+    }
+    rest = &tag[end + CLOSE.len()..];
+  }
+  output.push_str(rest);
+  Ok(output)
+}
+
+fn value(name: &str, options: &ScaffoldOptions) -> Option<String> {
+  match name {
+    "projectName" => Some(options.project_name.clone()),
+    "packageName" => Some(options.package_name.clone()),
+    _ => feature(name, options).map(|value| value.to_string()),
+  }
+}
+
+fn escape_html(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[test]
+fn test_substitute() {
+  let options = ScaffoldOptions {
+    project_name: "My App".to_string(),
+    package_name: "my-app".to_string(),
+    needs_router: true,
+    ..Default::default()
+  };
+
+  assert_eq!(
+    substitute("<h1>{{ msg }}</h1>\n", &options, false).unwrap(),
+    "<h1>{{ msg }}</h1>\n"
+  );
+  assert_eq!(
+    substitute(
+      "<title><%= projectName %></title> <%=packageName%>\n",
+      &options,
+      false
+    )
+    .unwrap(),
+    "<title>My App</title> my-app\n"
+  );
+  assert_eq!(
+    substitute(
+      "a\n<% if router %>\nb <%= typescript %>\n  <% if typescript %>\nc\n  <% else %>\nd\n  <% endif %>\n<% else %>\ne\n<% endif %>\nf",
+      &options,
+      false
+    )
+    .unwrap(),
+    "a\nb false\nd\nf"
+  );
+
+  // unknown variables and escaped tags are kept
+  assert_eq!(
+    substitute(
+      "<link href=\"<%= BASE_URL %>favicon.ico\"> <%%= projectName %> <%% if x %>\n<%% endif %>\n",
+      &options,
+      false
+    )
+    .unwrap(),
+    "<link href=\"<%= BASE_URL %>favicon.ico\"> <%= projectName %> <% if x %>\n<% endif %>\n"
+  );
+
+  let options = ScaffoldOptions {
+    project_name: "Tom & Jerry's <App>".to_string(),
+    ..options
+  };
+  assert_eq!(
+    substitute("<title><%= projectName %></title>\n", &options, true).unwrap(),
+    "<title>Tom &amp; Jerry&#39;s &lt;App&gt;</title>\n"
+  );
+
+  for (template, message) in [
+    ("<%= name", "unclosed `<%=`"),
+    ("<% if router %>\n", "`if` without `endif`"),
+    ("<% endif %>\n", "`endif` without `if`"),
+    ("<% for x %>\n", "unknown directive `for`"),
+    ("<% if routr %>\n<% endif %>\n", "unknown feature `routr`"),
+  ] {
+    let err = substitute(template, &options, false).unwrap_err();
+    assert!(err.contains(message), "{:?}: {}", template, err);
+  }
+}
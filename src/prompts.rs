@@ -1,158 +1,162 @@
-use std::fmt::Display;
-
+use create_vue_rust::{
+  utils::{can_skip_emptying, get_path_from_cwd, is_valid_package_name, to_valid_package_name},
+  vfs::DiskFs,
+  Conflict, E2eTesting, Error, PackageManager, Resolution, Result, ScaffoldOptions, StyleGuide,
+};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 
-use crate::{
-  args::Args,
-  utils::{can_skip_emptying, is_valid_package_name, to_valid_package_name},
+use std::{
+  io::{self, IsTerminal},
+  path::Path,
 };
-use anyhow::Result;
+
+use crate::args::Args;
 use console::Term;
 
 #[derive(Debug)]
 pub struct Prompts {
   pub project_name: String,
-  pub should_overwrite: bool,
-  pub package_name: String,
-  pub needs_type_script: bool,
-  pub needs_jsx: bool,
-  pub needs_router: bool,
-  pub needs_pinia: bool,
-  pub needs_vitest: bool,
-  pub needs_e2e_testing: E2eTesting,
-  pub needs_eslint: bool,
-  pub needs_prettier: bool,
+  /// How to handle the existing files, `None` when the target directory is empty.
+  pub existing_files: Option<ConflictMode>,
+  pub options: ScaffoldOptions,
+  pub needs_git: bool,
 }
 
-#[derive(Debug, Clone)]
-pub enum E2eTesting {
-  None,
-  Cypress,
-  Playwright,
+/// How to scaffold into a target directory that is not empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictMode {
+  /// Remove every existing file except `.git`.
+  Replace,
+  /// Keep the existing files the templates would change.
+  Skip,
+  /// Overwrite the existing files the templates would change.
+  Overwrite,
+  /// Ask for each existing file the templates would change.
+  Ask,
 }
 
-impl Display for E2eTesting {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    match self {
-      E2eTesting::None => write!(f, "No"),
-      E2eTesting::Cypress => write!(f, "Cypress"),
-      E2eTesting::Playwright => write!(f, "Playwright"),
-    }
-  }
-}
+const DEFAULT_PROJECT_NAME: &str = "my-vue-app";
 
 impl Prompts {
-  pub fn new(args: &Args) -> Self {
+  pub fn new(args: &Args) -> Result<Self> {
+    let interactive = !args.yes && io::stdin().is_terminal();
+
     let project_name = match args.project_name.clone() {
       Some(name) => name,
-      None => get_string_from_user("Project name", "my-vue-app").unwrap(),
+      None if interactive => get_string_from_user("Project name", DEFAULT_PROJECT_NAME)?,
+      None => DEFAULT_PROJECT_NAME.to_string(),
     };
 
-    let is_feature_flags_used = &args.default
-      | &args.ts
-      | &args.jsx
-      | &args.router
-      | &args.pinia
-      | &args.tests
-      | &args.vitest
-      | &args.cypress
-      | &args.playwright
-      | &args.eslint;
-
-    let show_should_overwrite = can_skip_emptying(&project_name).unwrap() | &args.force;
+    let is_empty = can_skip_emptying(&DiskFs, &get_path_from_cwd(&project_name)?)?;
 
-    let should_overwrite = if show_should_overwrite {
-      false
+    let existing_files = if is_empty {
+      None
+    } else if args.skip_existing {
+      Some(ConflictMode::Skip)
+    } else if args.overwrite_existing {
+      Some(ConflictMode::Overwrite)
+    } else if args.force {
+      Some(ConflictMode::Replace)
+    } else if !interactive {
+      return Err(Error::MissingAnswer(format!(
+        "Target directory {} is not empty, pass --force, --skip-existing or --overwrite-existing \
+         to scaffold into it anyway",
+        project_name
+      )));
     } else {
-      let x = get_should_overwrite(&project_name).unwrap_or(false);
-      if !x {
-        panic!("Operation cancelled")
-      }
-      x
+      Some(get_conflict_mode(&project_name)?)
     };
 
-    let package_name = match is_valid_package_name(&project_name) {
-      Ok(true) => project_name.clone(),
-      Ok(false) => {
-        let name = get_string_from_user(
-          "Package name:",
-          to_valid_package_name(&project_name).unwrap().as_str(),
-        )
-        .unwrap();
-        match is_valid_package_name(&name) {
-          Ok(true) => name,
-          _ => panic!("Invalid package name"),
-        }
-      }
-      _ => panic!(" Operation cancelled"),
-    };
+    // the directory name, also when the project is created in `.`
+    let display_name = get_path_from_cwd(&project_name)?
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_else(|| project_name.clone());
 
-    let needs_type_script = if is_feature_flags_used {
-      args.ts
+    let package_name = if is_valid_package_name(&display_name) {
+      display_name.clone()
+    } else if interactive {
+      get_string_from_user("Package name:", &to_valid_package_name(&display_name))?
     } else {
-      get_bool_from_user("Add TypeScript Support?", false).unwrap()
+      to_valid_package_name(&display_name)
     };
 
-    let needs_jsx = if is_feature_flags_used {
-      args.jsx
-    } else {
-      get_bool_from_user("Add JSX Support?", false).unwrap()
-    };
+    if !is_valid_package_name(&package_name) {
+      return Err(Error::InvalidName(package_name));
+    }
 
-    let needs_router = if is_feature_flags_used {
-      args.router
+    let options = if args.is_feature_flags_used() || !interactive {
+      args.resolve_options(display_name, package_name)
     } else {
-      get_bool_from_user(
-        "Add Vue Router for Single Page Application development?",
-        false,
-      )
-      .unwrap()
+      get_options_from_user(args, display_name, package_name)?
     };
 
-    let needs_pinia = if is_feature_flags_used {
-      args.pinia
+    let needs_git = if args.git {
+      true
+    } else if args.no_git || !interactive {
+      false
     } else {
-      get_bool_from_user("Add Pinia for state management?", false).unwrap()
+      get_bool_from_user("Initialize a git repository?", false)?
     };
 
-    let needs_vitest = if is_feature_flags_used {
-      args.vitest
-    } else {
-      get_bool_from_user("Add Vitest for Unit Testing?", false).unwrap()
-    };
+    Ok(Self {
+      project_name,
+      existing_files,
+      options,
+      needs_git,
+    })
+  }
+}
 
-    let needs_e2e_testing = if is_feature_flags_used {
-      E2eTesting::None
-    } else {
-      get_e2e_testing()
-    };
+fn get_options_from_user(
+  args: &Args,
+  project_name: String,
+  package_name: String,
+) -> Result<ScaffoldOptions> {
+  let needs_type_script = get_bool_from_user("Add TypeScript Support?", false)?;
 
-    let needs_eslint = if is_feature_flags_used {
-      args.eslint
-    } else {
-      get_bool_from_user("Add ESLint for code quality?", false).unwrap()
-    };
+  let needs_jsx = get_bool_from_user("Add JSX Support?", false)?;
 
-    let needs_prettier = if is_feature_flags_used | !needs_eslint {
-      args.eslint_with_prettier
-    } else {
-      get_bool_from_user("Add Prettier for code formatting?", false).unwrap()
-    };
+  let needs_router = get_bool_from_user(
+    "Add Vue Router for Single Page Application development?",
+    false,
+  )?;
 
-    Self {
-      project_name,
-      should_overwrite,
-      package_name,
-      needs_type_script,
-      needs_jsx,
-      needs_router,
-      needs_pinia,
-      needs_vitest,
-      needs_e2e_testing,
-      needs_eslint,
-      needs_prettier,
-    }
-  }
+  let needs_pinia = get_bool_from_user("Add Pinia for state management?", false)?;
+
+  let needs_vitest = get_bool_from_user("Add Vitest for Unit Testing?", false)?;
+
+  let needs_e2e_testing = get_e2e_testing()?;
+
+  let needs_eslint = get_bool_from_user("Add ESLint for code quality?", false)?;
+
+  let eslint_style = if needs_eslint {
+    get_style_guide()?
+  } else {
+    StyleGuide::Default
+  };
+
+  let needs_prettier = if needs_eslint {
+    get_bool_from_user("Add Prettier for code formatting?", false)?
+  } else {
+    false
+  };
+
+  Ok(ScaffoldOptions {
+    project_name,
+    package_name,
+    needs_type_script,
+    needs_jsx,
+    needs_router,
+    needs_pinia,
+    needs_vitest,
+    needs_e2e_testing,
+    needs_eslint,
+    needs_prettier,
+    eslint_style,
+    templates: args.templates.clone(),
+    package_manager: args.package_manager.unwrap_or_else(PackageManager::detect),
+  })
 }
 
 fn get_string_from_user(prompt: &str, default: &str) -> Result<String> {
@@ -160,29 +164,88 @@ fn get_string_from_user(prompt: &str, default: &str) -> Result<String> {
   let name = Input::<String>::with_theme(default_theme)
     .default(default.to_string())
     .with_prompt(prompt)
-    .interact()
-    .unwrap();
+    .interact()?;
   Ok(name)
 }
 
-fn get_should_overwrite(package_name: &String) -> Result<bool> {
-  let default_theme = &ColorfulTheme::default();
+fn get_conflict_mode(package_name: &str) -> Result<ConflictMode> {
   let target_dir = if package_name == "." {
-    "current directory".to_string()
+    "Current directory".to_string()
   } else {
-    format!("directory {}", &package_name)
+    format!("Target directory {}", &package_name)
   };
 
-  let notice_str = format!(
-    "{},is not empty. Remove existing files and continue?",
-    target_dir
-  );
-  let should_overwrite = Confirm::with_theme(default_theme)
-    .default(false)
-    .with_prompt(notice_str)
-    .interact()
-    .unwrap();
-  Ok(should_overwrite)
+  let items = [
+    ConflictMode::Replace,
+    ConflictMode::Ask,
+    ConflictMode::Skip,
+    ConflictMode::Overwrite,
+  ];
+  let labels = [
+    "Remove existing files and continue",
+    "Decide for each conflicting file",
+    "Keep existing files, add only new ones",
+    "Overwrite conflicting files, keep all others",
+    "Cancel operation",
+  ];
+
+  let conflict_mode = Select::with_theme(&ColorfulTheme::default())
+    .with_prompt(format!("{} is not empty. How should existing files be handled?", target_dir))
+    .items(&labels)
+    .default(1)
+    .interact_on_opt(&Term::stderr())?;
+
+  match conflict_mode {
+    Some(index) if index < items.len() => Ok(items[index]),
+    _ => Err(Error::Cancelled),
+  }
+}
+
+/// Decides what happens to an existing file the templates would change, asking the user in
+/// [`ConflictMode::Ask`]. With `merge_json`, JSON files are always deep-merged.
+pub fn resolve_conflict(
+  mode: ConflictMode,
+  merge_json: bool,
+  root: &Path,
+  conflict: &Conflict,
+) -> Result<Resolution> {
+  if merge_json && conflict.is_json() {
+    return Ok(Resolution::Merge);
+  }
+
+  match mode {
+    ConflictMode::Replace | ConflictMode::Skip => Ok(Resolution::Keep),
+    ConflictMode::Overwrite => Ok(Resolution::Overwrite),
+    ConflictMode::Ask => get_resolution(root, conflict),
+  }
+}
+
+fn get_resolution(root: &Path, conflict: &Conflict) -> Result<Resolution> {
+  let path = conflict.path.strip_prefix(root).unwrap_or(&conflict.path);
+
+  let mut items = vec![
+    ("Keep the existing file", Some(Resolution::Keep)),
+    ("Overwrite it", Some(Resolution::Overwrite)),
+  ];
+  if conflict.is_json() {
+    items.push(("Deep-merge the template into it", Some(Resolution::Merge)));
+  }
+  items.push(("Show the diff", None));
+  let labels = items.iter().map(|(label, _)| *label).collect::<Vec<_>>();
+
+  loop {
+    let resolution = Select::with_theme(&ColorfulTheme::default())
+      .with_prompt(format!("{} already exists", path.display()))
+      .items(&labels)
+      .default(0)
+      .interact_on_opt(&Term::stderr())?;
+
+    match resolution.map(|index| items[index].1) {
+      Some(Some(resolution)) => return Ok(resolution),
+      Some(None) => eprintln!("{}", conflict.diff()),
+      None => return Err(Error::Cancelled),
+    }
+  }
 }
 
 fn get_bool_from_user(prompt: &str, default: bool) -> Result<bool> {
@@ -190,12 +253,11 @@ fn get_bool_from_user(prompt: &str, default: bool) -> Result<bool> {
   let should_overwrite = Confirm::with_theme(default_theme)
     .default(default)
     .with_prompt(prompt)
-    .interact()
-    .unwrap();
+    .interact()?;
   Ok(should_overwrite)
 }
 
-fn get_e2e_testing() -> E2eTesting {
+fn get_e2e_testing() -> Result<E2eTesting> {
   let items = vec![
     E2eTesting::None,
     E2eTesting::Cypress,
@@ -206,11 +268,62 @@ fn get_e2e_testing() -> E2eTesting {
     .with_prompt("Add an End-to-End Testing Solution?")
     .items(&items)
     .default(0)
-    .interact_on_opt(&Term::stderr())
-    .unwrap();
+    .interact_on_opt(&Term::stderr())?;
 
   match e2e_testing {
-    Some(index) => items.get(index).unwrap().clone(),
-    None => E2eTesting::None,
+    Some(index) => Ok(items[index].clone()),
+    None => Err(Error::Cancelled),
+  }
+}
+
+fn get_style_guide() -> Result<StyleGuide> {
+  let items = [StyleGuide::Default, StyleGuide::Airbnb, StyleGuide::Standard];
+  let labels = ["Default (eslint:recommended)", "Airbnb", "Standard"];
+
+  let style_guide = Select::with_theme(&ColorfulTheme::default())
+    .with_prompt("Pick an ESLint style guide")
+    .items(&labels)
+    .default(0)
+    .interact_on_opt(&Term::stderr())?;
+
+  match style_guide {
+    Some(index) => Ok(items[index].clone()),
+    None => Err(Error::Cancelled),
   }
 }
+
+#[test]
+fn test_non_interactive_defaults() {
+  use clap::Parser;
+
+  let args = Args::try_parse_from(["create-vue-rust", "--yes", "--router"]).unwrap();
+  let prompts = Prompts::new(&args).unwrap();
+  assert_eq!(prompts.project_name, DEFAULT_PROJECT_NAME);
+  assert_eq!(prompts.options.package_name, DEFAULT_PROJECT_NAME);
+  assert_eq!(prompts.options.project_name, DEFAULT_PROJECT_NAME);
+  assert!(prompts.existing_files.is_none());
+  assert!(!prompts.needs_git);
+  assert!(prompts.options.needs_router);
+  assert!(!prompts.options.needs_type_script);
+
+  let args = Args::try_parse_from(["create-vue-rust", "--yes", "src"]).unwrap();
+  assert!(matches!(Prompts::new(&args), Err(Error::MissingAnswer(_))));
+}
+
+#[test]
+fn test_current_directory() {
+  use clap::Parser;
+
+  let dir_name = std::env::current_dir()
+    .unwrap()
+    .file_name()
+    .unwrap()
+    .to_string_lossy()
+    .into_owned();
+
+  let args = Args::try_parse_from(["create-vue-rust", ".", "--yes", "--force"]).unwrap();
+  let prompts = Prompts::new(&args).unwrap();
+  assert_eq!(prompts.project_name, ".");
+  assert_eq!(prompts.options.project_name, dir_name);
+  assert_eq!(prompts.options.package_name, to_valid_package_name(&dir_name));
+}
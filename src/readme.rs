@@ -0,0 +1,159 @@
+use serde_json::Value;
+
+use crate::scaffold::{E2eTesting, ScaffoldOptions};
+
+/// Renders the `README.md` of a new project, like upstream create-vue does.
+///
+/// Every script section is only added when `pkg`, the final `package.json`, has that script,
+/// and the commands use the project's package manager.
+pub fn generate_readme(options: &ScaffoldOptions, pkg: &Value) -> String {
+  let pm = options.package_manager;
+  let has_script = |name: &str| pkg["scripts"].get(name).is_some();
+  let commands = |lines: &[String]| format!("```sh\n{}\n```\n", lines.join("\n"));
+
+  let mut sections = vec![format!(
+    "# {}\n\nThis template should help get you started developing with Vue 3 in Vite.\n",
+    options.project_name
+  )];
+
+  sections.push(
+    "## Recommended IDE Setup\n\n\
+     [VSCode](https://code.visualstudio.com/) + \
+     [Volar](https://marketplace.visualstudio.com/items?itemName=Vue.volar) (and disable Vetur) + \
+     [TypeScript Vue Plugin (Volar)](https://marketplace.visualstudio.com/items?itemName=Vue.vscode-typescript-vue-plugin).\n"
+      .to_string(),
+  );
+
+  if options.needs_type_script {
+    sections.push(
+      "## Type Support for `.vue` Imports in TS\n\n\
+       TypeScript cannot handle type information for `.vue` imports by default, so we replace \
+       the `tsc` CLI with `vue-tsc` for type checking. In editors, we need \
+       [TypeScript Vue Plugin (Volar)](https://marketplace.visualstudio.com/items?itemName=Vue.vscode-typescript-vue-plugin) \
+       to make the TypeScript language service aware of `.vue` types.\n"
+        .to_string(),
+    );
+  }
+
+  sections.push(
+    "## Customize configuration\n\nSee [Vite Configuration Reference](https://vitejs.dev/config/).\n"
+      .to_string(),
+  );
+
+  sections.push(format!("## Project Setup\n\n{}", commands(&[pm.install()])));
+
+  if has_script("dev") {
+    sections.push(format!(
+      "### Compile and Hot-Reload for Development\n\n{}",
+      commands(&[pm.run("dev")])
+    ));
+  }
+
+  if has_script("build") {
+    let title = if has_script("type-check") {
+      "Type-Check, Compile and Minify for Production"
+    } else {
+      "Compile and Minify for Production"
+    };
+    sections.push(format!("### {}\n\n{}", title, commands(&[pm.run("build")])));
+  }
+
+  if has_script("test:unit") {
+    let section = if options.needs_vitest {
+      format!(
+        "### Run Unit Tests with [Vitest](https://vitest.dev/)\n\n{}",
+        commands(&[pm.run("test:unit")])
+      )
+    } else {
+      format!(
+        "### Run Headed Component Tests with [Cypress Component Testing](https://on.cypress.io/component)\n\n{}",
+        commands(&[format!(
+          "{} # or `{}` for headless testing",
+          pm.run("test:unit:dev"),
+          pm.run("test:unit")
+        )])
+      )
+    };
+    sections.push(section);
+  }
+
+  if has_script("test:e2e") {
+    let extension = if options.needs_type_script {
+      "ts"
+    } else {
+      "js"
+    };
+    let section = match options.needs_e2e_testing {
+      E2eTesting::Playwright => format!(
+        "### Run End-to-End Tests with [Playwright](https://playwright.dev)\n\n{}",
+        commands(&[
+          "# Install browsers for the first run".to_string(),
+          pm.exec("playwright install"),
+          String::new(),
+          "# When testing on CI, must build the project first".to_string(),
+          pm.run("build"),
+          String::new(),
+          "# Runs the end-to-end tests".to_string(),
+          pm.run("test:e2e"),
+          "# Runs the tests only on Chromium".to_string(),
+          pm.run_with_args("test:e2e", "--project=chromium"),
+          "# Runs the tests of a specific file".to_string(),
+          pm.run_with_args("test:e2e", &format!("e2e/vue.spec.{}", extension)),
+          "# Runs the tests in debug mode".to_string(),
+          pm.run_with_args("test:e2e", "--debug"),
+        ])
+      ),
+      _ => format!(
+        "### Run End-to-End Tests with [Cypress](https://www.cypress.io/)\n\n{}\n\
+         This runs the end-to-end tests against the Vite development server.\n\
+         It is much faster than the production build.\n\n\
+         But it's still recommended to test the production build with `test:e2e` before \
+         deploying (e.g. in CI environments):\n\n{}",
+        commands(&[pm.run("test:e2e:dev")]),
+        commands(&[pm.run("build"), pm.run("test:e2e")])
+      ),
+    };
+    sections.push(section);
+  }
+
+  if has_script("lint") {
+    sections.push(format!(
+      "### Lint with [ESLint](https://eslint.org/)\n\n{}",
+      commands(&[pm.run("lint")])
+    ));
+  }
+
+  sections.join("\n")
+}
+
+#[test]
+fn test_generate_readme() {
+  use crate::package_manager::PackageManager;
+
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    needs_e2e_testing: E2eTesting::Playwright,
+    package_manager: PackageManager::Pnpm,
+    ..Default::default()
+  };
+  let pkg = serde_json::json!({
+    "scripts": {
+      "dev": "vite",
+      "build": "run-p type-check build-only",
+      "type-check": "vue-tsc --noEmit",
+      "test:e2e": "playwright test"
+    }
+  });
+  let readme = generate_readme(&options, &pkg);
+
+  assert!(readme.starts_with("# vue-project\n"));
+  assert!(readme.contains("## Type Support for `.vue` Imports in TS"));
+  assert!(readme.contains("## Project Setup\n\n```sh\npnpm install\n```\n"));
+  assert!(
+    readme.contains("### Type-Check, Compile and Minify for Production\n\n```sh\npnpm build\n```")
+  );
+  assert!(readme.contains("pnpm exec playwright install\n"));
+  assert!(readme.contains("pnpm test:e2e e2e/vue.spec.ts\n"));
+  assert!(!readme.contains("Unit Tests"));
+  assert!(!readme.contains("ESLint"));
+}
@@ -1,75 +1,122 @@
-use std::{
-  fs::{self, File},
-  io::BufReader,
-  path::PathBuf,
-};
+use std::{borrow::Cow, path::Path};
 
-use anyhow::Result;
+use crate::{
+  error::{Error, Result},
+  placeholders::substitute,
+  scaffold::ScaffoldOptions,
+  vfs::FileSystem,
+};
+use include_dir::{Dir, DirEntry};
 use serde_json::{json, Value};
 
-pub fn render_template(src: &PathBuf, dest: &PathBuf) -> Result<()> {
-  if src.is_dir() {
-    if src.file_name().unwrap().to_str().unwrap() == "node_modules" {
-      return Ok(());
+pub fn render_template(
+  src: &Dir,
+  dest: &Path,
+  options: &ScaffoldOptions,
+  fs: &mut dyn FileSystem,
+) -> Result<()> {
+  for entry in src.entries() {
+    let file_name = match entry.path().file_name().and_then(|name| name.to_str()) {
+      Some(name) => name,
+      None => continue,
+    };
+
+    match entry {
+      DirEntry::Dir(dir) => {
+        if is_ignored_dir(file_name) {
+          continue;
+        }
+        render_template(dir, &dest.join(file_name), options, fs)?;
+      }
+      DirEntry::File(file) => render_file(file_name, file.contents(), dest, options, fs)?,
     }
+  }
+
+  Ok(())
+}
 
-    match fs::create_dir(dest) {
-      Ok(_) => {}
-      _ => {}
+/// Like [`render_template`], but for a template directory read from `source`, such as a
+/// custom template on disk.
+pub fn render_template_dir(
+  src: &Path,
+  dest: &Path,
+  source: &dyn FileSystem,
+  options: &ScaffoldOptions,
+  fs: &mut dyn FileSystem,
+) -> Result<()> {
+  for path in source.read_dir(src)? {
+    let file_name = match path.file_name().and_then(|name| name.to_str()) {
+      Some(name) => name,
+      None => continue,
     };
-    let read_dir = fs::read_dir(src).unwrap();
 
-    for file in read_dir {
-      if let Ok(entry) = file {
-        let path = entry.path();
-        let dest_path = dest.join(path.file_name().unwrap());
-        render_template(&path, &dest_path).unwrap();
+    if source.is_dir(&path) {
+      if is_ignored_dir(file_name) {
+        continue;
       }
+      render_template_dir(&path, &dest.join(file_name), source, options, fs)?;
+    } else if let Some(contents) = source.read(&path)? {
+      render_file(file_name, &contents, dest, options, fs)?;
     }
-    return Ok(());
   }
 
-  let file_name = src.file_name().unwrap();
+  Ok(())
+}
 
-  if file_name == "package.json" {
-    let file1 = File::open(&src)?;
-    let reader1 = BufReader::new(file1);
+fn is_ignored_dir(name: &str) -> bool {
+  name == "node_modules" || name == ".git"
+}
 
-    let pkg = serde_json::from_reader(reader1)?;
+fn render_file(
+  file_name: &str,
+  contents: &[u8],
+  dest_dir: &Path,
+  options: &ScaffoldOptions,
+  fs: &mut dyn FileSystem,
+) -> Result<()> {
+  let dest = dest_dir.join(file_name);
 
-    let file2 = File::open(&dest)?;
-    let reader2 = BufReader::new(file2);
-    let pkg2 = serde_json::from_reader(reader2)?;
+  // fill in the placeholders of text files, binary files like images are copied as they are
+  let contents = match std::str::from_utf8(contents) {
+    Ok(text) if !text.contains('\0') => {
+      let html = Path::new(file_name).extension().is_some_and(|ext| ext == "html");
+      let text = substitute(text, options, html)
+        .map_err(|message| Error::InvalidTemplate(format!("{}: {}", dest.display(), message)))?;
+      Cow::Owned(text.into_bytes())
+    }
+    _ => Cow::Borrowed(contents),
+  };
+  let contents = contents.as_ref();
 
-    let value = merge(&pkg, &pkg2).unwrap();
-    let value = sort_pkg(&value).unwrap();
-    let content = serde_json::to_string_pretty(&value).unwrap();
+  if file_name == "package.json" {
+    if let Some(existing) = fs.read(&dest)? {
+      let existing: Value = serde_json::from_slice(&existing)?;
+      let pkg: Value = serde_json::from_slice(contents)?;
 
-    fs::write(dest, content).unwrap();
-    return Ok(());
-  }
+      // later layers take precedence, e.g. the typescript `build` script
+      let value = merge(&existing, &pkg)?;
+      let value = sort_pkg(&value)?;
+      let content = serde_json::to_string_pretty(&value)?;
 
-  let mut dest = dest.clone();
-  if file_name.to_str().unwrap().starts_with("_") {
-    dest = dest.to_str().unwrap().replace("_", ".").into();
+      return fs.write(&dest, content.as_bytes());
+    }
   }
 
+  let dest = match file_name.strip_prefix('_') {
+    Some(name) => dest_dir.join(format!(".{}", name)),
+    None => dest,
+  };
 
-  if file_name == "_gitignore" && dest.is_file() {
-    let old_ignore = fs::read_to_string(&dest).unwrap();
-    let new_ignore = fs::read_to_string(&src).unwrap();
+  if file_name == "_gitignore" {
+    if let Some(old_ignore) = fs.read_to_string(&dest)? {
+      let new_ignore = String::from_utf8_lossy(contents);
 
-    let contents = format!("{}\n{}", old_ignore, new_ignore);
-    fs::write(&dest, contents)?;
-    
-    return Ok(());
+      let contents = format!("{}\n{}", old_ignore, new_ignore);
+      return fs.write(&dest, contents.as_bytes());
+    }
   }
 
-  println!("file_name {:?}!!!!,{:?},{:?}",file_name,dest,dest.is_file());
-
-  fs::copy(src, dest).unwrap();
-
-  Ok(())
+  fs.write(&dest, contents)
 }
 
 pub fn merge(obj1: &Value, obj2: &Value) -> Result<Value> {
@@ -101,7 +148,7 @@ pub fn merge(obj1: &Value, obj2: &Value) -> Result<Value> {
   Ok(obj1)
 }
 
-fn sort_pkg(pkg: &Value) -> Result<Value> {
+pub fn sort_pkg(pkg: &Value) -> Result<Value> {
   let mut res = json!({});
 
   let dep_keys = vec![
@@ -111,17 +158,23 @@ fn sort_pkg(pkg: &Value) -> Result<Value> {
     "optionalDependencies",
   ];
 
-  for key in pkg.as_object().unwrap().keys() {
+  let fields = pkg
+    .as_object()
+    .ok_or_else(|| Error::Merge("package.json is not an object".to_string()))?;
+  for key in fields.keys() {
     if !dep_keys.contains(&key.as_str()) {
       res[key] = pkg[key].clone()
     }
   }
 
   for key in dep_keys {
-    if pkg.get(key).is_some() {
+    if let Some(deps) = pkg.get(key) {
+      let deps = deps
+        .as_object()
+        .ok_or_else(|| Error::Merge(format!("`{}` in package.json is not an object", key)))?;
       res[key] = json!({});
 
-      let mut keys = pkg[key].as_object().unwrap().keys().collect::<Vec<_>>();
+      let mut keys = deps.keys().collect::<Vec<_>>();
 
       keys.sort();
       for k in keys {
@@ -131,3 +184,83 @@ fn sort_pkg(pkg: &Value) -> Result<Value> {
   }
   Ok(res)
 }
+
+#[test]
+fn test_render_template_dir() {
+  use crate::vfs::MemoryFs;
+
+  let template = Path::new("/house-template");
+  let mut source = MemoryFs::new();
+  source
+    .write(&template.join("package.json"), br#"{"dependencies":{"axios":"^1.2.0"}}"#)
+    .unwrap();
+  source.write(&template.join("_gitignore"), b"*.local\n").unwrap();
+  source.write(&template.join("src/plugins/axios.js"), b"").unwrap();
+  source.write(&template.join(".git/HEAD"), b"").unwrap();
+
+  let dest = Path::new("/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(&dest.join("package.json"), br#"{"name":"vue-project"}"#)
+    .unwrap();
+  fs.write(&dest.join(".gitignore"), b"dist\n").unwrap();
+  render_template_dir(template, dest, &source, &ScaffoldOptions::default(), &mut fs).unwrap();
+
+  let pkg: Value = serde_json::from_slice(&fs.read(&dest.join("package.json")).unwrap().unwrap())
+    .unwrap();
+  assert_eq!(pkg["name"], "vue-project");
+  assert_eq!(pkg["dependencies"]["axios"], "^1.2.0");
+  assert_eq!(
+    fs.read_to_string(&dest.join(".gitignore")).unwrap().unwrap(),
+    "dist\n\n*.local\n"
+  );
+  assert!(fs.is_file(&dest.join("src/plugins/axios.js")));
+  assert!(!fs.is_dir(&dest.join(".git")));
+}
+
+#[test]
+fn test_render_invalid_dependencies() {
+  use crate::vfs::MemoryFs;
+
+  let template = Path::new("/tpl");
+  let mut source = MemoryFs::new();
+  source
+    .write(&template.join("package.json"), br#"{"dependencies":"oops"}"#)
+    .unwrap();
+
+  let dest = Path::new("/p2");
+  let mut fs = MemoryFs::new();
+  fs.write(&dest.join("package.json"), br#"{"name":"p2"}"#).unwrap();
+  assert!(matches!(
+    render_template_dir(template, dest, &source, &ScaffoldOptions::default(), &mut fs),
+    Err(Error::Merge(_))
+  ));
+  assert!(matches!(sort_pkg(&json!([])), Err(Error::Merge(_))));
+}
+
+#[test]
+fn test_later_layers_win() {
+  use crate::vfs::MemoryFs;
+
+  let template = Path::new("/typescript");
+  let mut source = MemoryFs::new();
+  source
+    .write(
+      &template.join("package.json"),
+      br#"{"scripts":{"build":"run-p type-check build-only"}}"#,
+    )
+    .unwrap();
+
+  let dest = Path::new("/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(
+    &dest.join("package.json"),
+    br#"{"name":"vue-project","scripts":{"dev":"vite","build":"vite build"}}"#,
+  )
+  .unwrap();
+  render_template_dir(template, dest, &source, &ScaffoldOptions::default(), &mut fs).unwrap();
+
+  let pkg: Value = serde_json::from_slice(&fs.read(&dest.join("package.json")).unwrap().unwrap())
+    .unwrap();
+  assert_eq!(pkg["scripts"]["dev"], "vite");
+  assert_eq!(pkg["scripts"]["build"], "run-p type-check build-only");
+}
@@ -1,26 +1,30 @@
-use anyhow::Result;
-use serde_json::json;
+use crate::error::Result;
+use serde_json::{json, Value};
 use std::path::PathBuf;
 
-use crate::create_eslint_config::{self, CreateConfig, StyleGuide};
+use crate::{
+  create_eslint_config::{CreateConfig, StyleGuide},
+  render::{merge, sort_pkg},
+  vfs::FileSystem,
+};
 
-struct RenderEslint {
-  root: PathBuf,
-  needs_typescript: bool,
-  needs_cypress: bool,
-  needs_cypress_ct: bool,
-  needs_prettier: bool,
+pub struct RenderEslint {
+  pub root: PathBuf,
+  pub needs_typescript: bool,
+  pub needs_cypress: bool,
+  pub needs_cypress_ct: bool,
+  pub needs_prettier: bool,
+  pub style_guide: StyleGuide,
 }
 
 impl RenderEslint {
-  fn render(&self) -> Result<()> {
+  pub fn render(&self, fs: &mut dyn FileSystem) -> Result<()> {
     let mut additional_config = json!({});
     let mut additional_dependencies = json!({});
 
     let root = self.root.clone();
 
     if self.needs_cypress {
-      let config = additional_config.as_object_mut().unwrap();
       let overrides_files = if self.needs_cypress_ct {
         vec![
           "**/__tests__/*.{cy,spec}.{js,ts,jsx,tsx}",
@@ -30,29 +34,75 @@ impl RenderEslint {
         vec!["cypress/e2e/**/*.{cy,spec}.{js,ts,jsx,tsx}"]
       };
 
-      let overrides = json!({
+      let overrides = json!([{
         "files":overrides_files,
         "extends": ["plugin:cypress/recommended"]
-      });
+      }]);
 
-      config.insert("overrides".to_string(), overrides);
-      let dep = additional_dependencies.as_object_mut().unwrap();
-
-      dep.insert("eslint-plugin-cypress".to_string(), json!("^2.12.1"));
+      additional_config["overrides"] = overrides;
+      additional_dependencies["devDependencies"] = json!({ "eslint-plugin-cypress": "^2.12.1" });
     };
 
     let config = CreateConfig {
-      style_guide: StyleGuide::Default,
+      style_guide: self.style_guide.clone(),
       has_typescript: self.needs_typescript,
       needs_prettier: self.needs_prettier,
       additional_config: Some(additional_config),
       additional_dependencies: Some(additional_dependencies),
     };
 
-    let (pkg,files) = config.create().unwrap();
+    let (pkg, files) = config.create()?;
+
+    let mut scripts = json!({
+      "lint": "eslint . --ext .vue,.js,.jsx,.cjs,.mjs,.ts,.tsx,.cts,.mts --fix --ignore-path .gitignore"
+    });
+
+    // only format `src/` by default, so no `.prettierignore` is needed
+    if self.needs_prettier {
+      scripts["format"] = json!("prettier --write src/");
+    }
+
+    // update package.json
+    let pkg_path = root.join("package.json");
+    let existing_pkg: Value = match fs.read(&pkg_path)? {
+      Some(contents) => serde_json::from_slice(&contents)?,
+      None => json!({}),
+    };
+    let updated_pkg = merge(&existing_pkg, pkg.get_value())?;
+    let updated_pkg = merge(&updated_pkg, &json!({ "scripts": scripts }))?;
+    let updated_pkg = sort_pkg(&updated_pkg)?;
+    fs.write(&pkg_path, serde_json::to_string_pretty(&updated_pkg)?.as_bytes())?;
+
+    // write .eslintrc.cjs, .prettierrc.json and .editorconfig
+    files.write_to(&root, fs)?;
 
-    files.write_to_disk(&root)?;
+    Ok(())
+  }
+}
 
-    todo!()
+#[test]
+fn test_render_eslint() {
+  use crate::vfs::MemoryFs;
+
+  let root = PathBuf::from("/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(&root.join("package.json"), br#"{"name":"vue-project"}"#)
+    .unwrap();
+  RenderEslint {
+    root: root.clone(),
+    needs_typescript: false,
+    needs_cypress: true,
+    needs_cypress_ct: false,
+    needs_prettier: false,
+    style_guide: StyleGuide::Default,
   }
+  .render(&mut fs)
+  .unwrap();
+
+  let pkg: Value = serde_json::from_slice(&fs.read(&root.join("package.json")).unwrap().unwrap())
+    .unwrap();
+  assert_eq!(pkg["devDependencies"]["eslint-plugin-cypress"], "^2.12.1");
+  assert!(pkg.get("eslint-plugin-cypress").is_none());
+  assert!(fs.is_file(&root.join(".eslintrc.cjs")));
+  assert!(fs.is_file(&root.join(".editorconfig")));
 }
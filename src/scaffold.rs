@@ -0,0 +1,389 @@
+use std::{
+  fmt::Display,
+  path::{Path, PathBuf},
+  sync::atomic::AtomicBool,
+};
+
+use serde_json::json;
+
+use crate::{
+  convert_typescript::{convert_to_typescript, remove_typescript_files},
+  create_eslint_config::StyleGuide,
+  error::{Error, Result},
+  manifest::{Manifest, MANIFEST_FILE},
+  package_manager::PackageManager,
+  readme::generate_readme,
+  render::{render_template, render_template_dir},
+  render_eslint::RenderEslint,
+  vfs::{DiskFs, FileSystem},
+  templates::{get_template, Layer},
+  transaction::stage,
+  utils::{can_skip_emptying, is_valid_package_name, mkdir},
+};
+
+#[derive(Debug, Clone)]
+pub enum E2eTesting {
+  None,
+  Cypress,
+  Playwright,
+}
+
+impl Display for E2eTesting {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      E2eTesting::None => write!(f, "No"),
+      E2eTesting::Cypress => write!(f, "Cypress"),
+      E2eTesting::Playwright => write!(f, "Playwright"),
+    }
+  }
+}
+
+/// The features of a generated project, independent of how they were chosen.
+#[derive(Debug, Clone)]
+pub struct ScaffoldOptions {
+  /// The name of the project directory, shown in the README and the page title.
+  pub project_name: String,
+  pub package_name: String,
+  pub needs_type_script: bool,
+  pub needs_jsx: bool,
+  pub needs_router: bool,
+  pub needs_pinia: bool,
+  pub needs_vitest: bool,
+  pub needs_e2e_testing: E2eTesting,
+  pub needs_eslint: bool,
+  pub needs_prettier: bool,
+  pub eslint_style: StyleGuide,
+  /// Extra template directories on disk, rendered in order after the built-in templates.
+  pub templates: Vec<PathBuf>,
+  /// The package manager whose commands the README shows.
+  pub package_manager: PackageManager,
+}
+
+impl Default for ScaffoldOptions {
+  fn default() -> Self {
+    Self {
+      project_name: "vue-project".to_string(),
+      package_name: "vue-project".to_string(),
+      needs_type_script: false,
+      needs_jsx: false,
+      needs_router: false,
+      needs_pinia: false,
+      needs_vitest: false,
+      needs_e2e_testing: E2eTesting::None,
+      needs_eslint: false,
+      needs_prettier: false,
+      eslint_style: StyleGuide::Default,
+      templates: vec![],
+      package_manager: PackageManager::Npm,
+    }
+  }
+}
+
+/// Generates a project into `target`, creating the directory if needed.
+///
+/// The project is staged next to `target` and only moved into place once it is complete, so a
+/// failure never leaves a half-written project behind. Fails with [`Error::TargetNotEmpty`]
+/// when `target` has files other than `.git`.
+pub fn scaffold(options: &ScaffoldOptions, target: &Path) -> Result<()> {
+  scaffold_cancellable(options, target, false, &AtomicBool::new(false))
+}
+
+/// Like [`scaffold`], but rolls back with [`Error::Cancelled`] as soon as `cancelled` is set.
+///
+/// With `replace`, the existing contents of `target` are replaced, except `.git`, instead of
+/// failing.
+pub fn scaffold_cancellable(
+  options: &ScaffoldOptions,
+  target: &Path,
+  replace: bool,
+  cancelled: &AtomicBool,
+) -> Result<()> {
+  if !replace && !can_skip_emptying(&DiskFs, target)? {
+    return Err(Error::TargetNotEmpty(target.to_path_buf()));
+  }
+
+  stage(target, &mut DiskFs, cancelled, |staging, fs| {
+    scaffold_into(options, staging, fs)
+  })
+}
+
+/// Writes every file of a project through `fs`, directly into `target`.
+///
+/// Existing files in `target` are overwritten, except `package.json` and `.gitignore`,
+/// which are merged with the rendered templates.
+pub fn scaffold_into(options: &ScaffoldOptions, target: &Path, fs: &mut dyn FileSystem) -> Result<()> {
+  let ScaffoldOptions {
+    package_name,
+    needs_type_script,
+    needs_vitest,
+    needs_e2e_testing,
+    needs_eslint,
+    needs_prettier,
+    eslint_style,
+    templates,
+    ..
+  } = options.clone();
+
+  if !is_valid_package_name(&package_name) {
+    return Err(Error::InvalidName(package_name));
+  }
+
+  if let Some(template) = templates.iter().find(|template| !DiskFs.is_dir(template)) {
+    return Err(Error::TemplateMissing(template.display().to_string()));
+  }
+
+  let needs_cypress = matches!(needs_e2e_testing, E2eTesting::Cypress);
+
+  let manifest = Manifest::builtin()?;
+
+  mkdir(fs, target)?;
+
+  let pkg = json!({
+    "name":package_name,
+    "version":"0.0.0",
+  });
+
+  let pkg_str = serde_json::to_string_pretty(&pkg)?;
+
+  fs.write(&target.join("package.json"), pkg_str.as_bytes())?;
+
+  // render the built-in layers the manifest selects for these options
+  for path in manifest.resolve(options)? {
+    // build.rs checks every path of the built-in manifest against the template tree
+    let layer = Layer::from_path(path).ok_or_else(|| Error::TemplateMissing(path.to_string()))?;
+    render_template(get_template(layer), target, options, fs)?;
+  }
+
+  // render eslint config
+  if needs_eslint {
+    RenderEslint {
+      root: target.to_path_buf(),
+      needs_typescript: needs_type_script,
+      needs_cypress,
+      needs_cypress_ct: needs_cypress && !needs_vitest,
+      needs_prettier,
+      style_guide: eslint_style,
+    }
+    .render(fs)?;
+  }
+
+  // render custom templates last, so they can replace the built-in files
+  let readme_path = target.join("README.md");
+  let readme = fs.read(&readme_path)?;
+  for template in &templates {
+    render_custom_template(template, options, target, fs)?;
+  }
+
+  // describe the final scripts, unless a custom template brought its own readme
+  if fs.read(&readme_path)? == readme {
+    let pkg = fs.read_to_string(&target.join("package.json"))?.unwrap_or_default();
+    let readme = generate_readme(options, &serde_json::from_str(&pkg)?);
+    fs.write(&readme_path, readme.as_bytes())?;
+  }
+
+  // share as many files as possible between the javascript and typescript templates
+  if needs_type_script {
+    convert_to_typescript(target, fs)?;
+  } else {
+    remove_typescript_files(target, fs)?;
+  }
+
+  Ok(())
+}
+
+/// Renders a custom template directory, either as a whole or, when it has a manifest of its
+/// own, the layers the manifest selects.
+fn render_custom_template(
+  template: &Path,
+  options: &ScaffoldOptions,
+  target: &Path,
+  fs: &mut dyn FileSystem,
+) -> Result<()> {
+  let manifest = match DiskFs.read_to_string(&template.join(MANIFEST_FILE))? {
+    Some(json) => Manifest::parse(&json)?,
+    None => return render_template_dir(template, target, &DiskFs, options, fs),
+  };
+
+  for layer in manifest.resolve(options)? {
+    let layer = template.join(layer);
+    if !DiskFs.is_dir(&layer) {
+      return Err(Error::TemplateMissing(layer.display().to_string()));
+    }
+    render_template_dir(&layer, target, &DiskFs, options, fs)?;
+  }
+  Ok(())
+}
+
+#[test]
+fn test_scaffold() {
+  use std::fs;
+
+  let target = std::env::temp_dir().join("create-vue-rust-scaffold");
+  let _ = fs::remove_dir_all(&target);
+
+  let options = ScaffoldOptions {
+    package_name: "invalid name".to_string(),
+    ..Default::default()
+  };
+  assert!(matches!(
+    scaffold(&options, &target),
+    Err(Error::InvalidName(_))
+  ));
+
+  let options = ScaffoldOptions {
+    templates: vec![target.join("missing-template")],
+    ..Default::default()
+  };
+  assert!(matches!(
+    scaffold(&options, &target),
+    Err(Error::TemplateMissing(_))
+  ));
+
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    needs_router: true,
+    ..Default::default()
+  };
+  fs::create_dir_all(target.join(".git")).unwrap();
+  fs::write(target.join("stale.txt"), "").unwrap();
+  assert!(matches!(
+    scaffold(&options, &target),
+    Err(Error::TargetNotEmpty(_))
+  ));
+  assert!(target.join("stale.txt").is_file());
+  scaffold_cancellable(&options, &target, true, &AtomicBool::new(false)).unwrap();
+
+  assert!(target.join(".git").is_dir());
+  assert!(!target.join("stale.txt").exists());
+  assert!(target.join("src/main.ts").is_file());
+  assert!(target.join("src/router/index.ts").is_file());
+  assert!(target.join("src/App.vue").is_file());
+  assert!(target.join(".gitignore").is_file());
+  let pkg = fs::read_to_string(target.join("package.json")).unwrap();
+  assert!(pkg.contains(r#""name": "vue-project""#));
+  assert!(pkg.contains(r#""build": "run-p type-check build-only""#));
+
+  fs::remove_dir_all(&target).unwrap();
+}
+
+#[test]
+fn test_scaffold_relative_target() {
+  use std::{env, fs};
+
+  let target = env::temp_dir().join("create-vue-rust-relative");
+  let _ = fs::remove_dir_all(&target);
+  fs::create_dir_all(&target).unwrap();
+
+  let cwd = env::current_dir().unwrap();
+  env::set_current_dir(&target).unwrap();
+  let result = scaffold(&ScaffoldOptions::default(), Path::new("."));
+  env::set_current_dir(cwd).unwrap();
+
+  result.unwrap();
+  assert!(target.join("package.json").is_file());
+  assert!(!target.join("..staging").exists());
+  assert!(!target.with_file_name(".create-vue-rust-relative.staging").exists());
+
+  fs::remove_dir_all(&target).unwrap();
+}
+
+#[test]
+fn test_scaffold_cypress() {
+  use crate::vfs::MemoryFs;
+
+  let target = Path::new("/vue-project");
+  let options = ScaffoldOptions {
+    needs_e2e_testing: E2eTesting::Cypress,
+    ..Default::default()
+  };
+  let mut fs = MemoryFs::new();
+  scaffold_into(&options, target, &mut fs).unwrap();
+
+  assert!(fs.is_file(&target.join("cypress.config.js")));
+  assert!(fs.is_file(&target.join("cypress/e2e/example.cy.js")));
+  let pkg: serde_json::Value =
+    serde_json::from_slice(&fs.read(&target.join("package.json")).unwrap().unwrap()).unwrap();
+  assert!(pkg["devDependencies"]["cypress"].is_string());
+  assert!(pkg["scripts"]["test:e2e"].is_string());
+}
+
+#[test]
+fn test_custom_template_manifest() {
+  use crate::vfs::MemoryFs;
+
+  let template = std::env::temp_dir().join("create-vue-rust-custom-template");
+  let mut disk = DiskFs;
+  let _ = disk.remove_dir_all(&template);
+  disk
+    .write(
+      &template.join(MANIFEST_FILE),
+      br#"{"layers": [{"path": "common"}, {"path": "typescript", "when": "typescript"}]}"#,
+    )
+    .unwrap();
+  disk.write(&template.join("common/src/plugins/index.js"), b"").unwrap();
+  disk.write(&template.join("typescript/src/shims.d.ts"), b"").unwrap();
+
+  let target = Path::new("/vue-project");
+  for needs_type_script in [false, true] {
+    let options = ScaffoldOptions {
+      needs_type_script,
+      templates: vec![template.clone()],
+      ..Default::default()
+    };
+    let mut fs = MemoryFs::new();
+    scaffold_into(&options, target, &mut fs).unwrap();
+
+    assert!(!fs.is_file(&target.join(MANIFEST_FILE)));
+    assert!(fs.is_dir(&target.join("src/plugins")));
+    assert_eq!(fs.is_file(&target.join("src/shims.d.ts")), needs_type_script);
+  }
+
+  disk.remove_dir_all(&template).unwrap();
+}
+
+#[test]
+fn test_scaffold_every_combination_in_memory() {
+  use crate::{snapshots::all_combinations, vfs::MemoryFs};
+
+  let target = Path::new("/vue-project");
+  for options in all_combinations() {
+    let mut fs = MemoryFs::new();
+    scaffold_into(&options, target, &mut fs).unwrap();
+
+    let (entry, stale_extension) = if options.needs_type_script {
+      ("src/main.ts", "js")
+    } else {
+      ("src/main.js", "ts")
+    };
+    assert!(fs.is_file(&target.join(entry)), "{:?}", options);
+    assert!(fs.is_file(&target.join("src/App.vue")), "{:?}", options);
+    assert!(
+      fs.files(target)
+        .unwrap()
+        .iter()
+        .all(|path| path.extension().is_none_or(|ext| ext != stale_extension)),
+      "{:?}",
+      options
+    );
+
+    let index_html = fs.read_to_string(&target.join("index.html")).unwrap().unwrap();
+    assert!(index_html.contains(entry), "{:?}", options);
+
+    let pkg: serde_json::Value =
+      serde_json::from_slice(&fs.read(&target.join("package.json")).unwrap().unwrap()).unwrap();
+    assert_eq!(pkg["name"], "vue-project");
+    assert_eq!(pkg["scripts"]["lint"].is_string(), options.needs_eslint);
+
+    let has_cypress_config = fs.files(target).unwrap().iter().any(|path| {
+      path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with("cypress.config."))
+    });
+    assert_eq!(
+      has_cypress_config,
+      matches!(options.needs_e2e_testing, E2eTesting::Cypress),
+      "{:?}",
+      options
+    );
+  }
+}
@@ -0,0 +1,270 @@
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  path::Path,
+};
+
+use similar::TextDiff;
+
+use crate::{
+  create_eslint_config::StyleGuide,
+  error::Result,
+  scaffold::{scaffold_into, E2eTesting, ScaffoldOptions},
+  vfs::{FileSystem, MemoryFs},
+};
+
+/// Every combination of features the CLI can produce. Prettier and the style guides are only
+/// offered together with ESLint.
+pub fn all_combinations() -> Vec<ScaffoldOptions> {
+  let mut lint_options = vec![(false, false, StyleGuide::Default)];
+  for eslint_style in [StyleGuide::Default, StyleGuide::Airbnb, StyleGuide::Standard] {
+    for needs_prettier in [false, true] {
+      lint_options.push((true, needs_prettier, eslint_style.clone()));
+    }
+  }
+
+  let mut combinations = vec![];
+  for bits in 0..(1 << 5) {
+    for (needs_eslint, needs_prettier, eslint_style) in &lint_options {
+      for needs_e2e_testing in [E2eTesting::None, E2eTesting::Cypress, E2eTesting::Playwright] {
+        let flag = |index: u32| bits & (1 << index) != 0;
+        combinations.push(ScaffoldOptions {
+          needs_type_script: flag(0),
+          needs_jsx: flag(1),
+          needs_router: flag(2),
+          needs_pinia: flag(3),
+          needs_vitest: flag(4),
+          needs_eslint: *needs_eslint,
+          needs_prettier: *needs_prettier,
+          eslint_style: eslint_style.clone(),
+          needs_e2e_testing,
+          ..Default::default()
+        });
+      }
+    }
+  }
+  combinations
+}
+
+/// A stable name for a feature combination, e.g. `ts-router-eslint-airbnb-cypress`.
+pub fn combination_name(options: &ScaffoldOptions) -> String {
+  let features = [
+    (options.needs_type_script, "ts"),
+    (options.needs_jsx, "jsx"),
+    (options.needs_router, "router"),
+    (options.needs_pinia, "pinia"),
+    (options.needs_vitest, "vitest"),
+    (options.needs_eslint, "eslint"),
+    (matches!(options.eslint_style, StyleGuide::Airbnb), "airbnb"),
+    (matches!(options.eslint_style, StyleGuide::Standard), "standard"),
+    (options.needs_prettier, "prettier"),
+    (
+      matches!(options.needs_e2e_testing, E2eTesting::Cypress),
+      "cypress",
+    ),
+    (
+      matches!(options.needs_e2e_testing, E2eTesting::Playwright),
+      "playwright",
+    ),
+  ];
+  let name = features
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join("-");
+  if name.is_empty() {
+    "default".to_string()
+  } else {
+    name
+  }
+}
+
+/// Renders every combination and compares it with the snapshots in `dir`.
+///
+/// Each combination is stored as `<name>.snap`, listing every file with a hash of its contents,
+/// and the contents themselves are stored once in `blobs/<hash>`. Returns a readable diff per
+/// drifted combination; with `update` the snapshots are rewritten instead.
+pub fn verify_templates(
+  dir: &Path,
+  update: bool,
+  fs: &mut dyn FileSystem,
+) -> Result<Vec<String>> {
+  let target = Path::new("/vue-project");
+  let blobs_dir = dir.join("blobs");
+
+  let mut drifts = vec![];
+  let mut snap_names = BTreeSet::new();
+  let mut blob_names = BTreeSet::new();
+
+  for options in all_combinations() {
+    let name = combination_name(&options);
+    let mut rendered = MemoryFs::new();
+    scaffold_into(&options, target, &mut rendered)?;
+
+    let mut files = BTreeMap::new();
+    for (path, contents) in rendered.iter() {
+      let relative = path.strip_prefix(target).unwrap_or(path);
+      let relative = relative.to_string_lossy().replace('\\', "/");
+      let hash = hash(contents);
+      if update {
+        fs.write(&blobs_dir.join(&hash), contents)?;
+      }
+      blob_names.insert(hash.clone());
+      files.insert(relative, (hash, contents.clone()));
+    }
+
+    let snap = files
+      .iter()
+      .map(|(path, (hash, _))| format!("{} {}\n", path, hash))
+      .collect::<String>();
+    let snap_path = dir.join(format!("{}.snap", name));
+    snap_names.insert(format!("{}.snap", name));
+
+    if update {
+      fs.write(&snap_path, snap.as_bytes())?;
+      continue;
+    }
+
+    let old_snap = fs.read_to_string(&snap_path)?.unwrap_or_default();
+    if old_snap != snap {
+      drifts.push(describe_drift(&name, &old_snap, &files, &blobs_dir, fs)?);
+    }
+  }
+
+  if update {
+    for path in fs.read_dir(dir)? {
+      let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+      if file_name.is_some_and(|name| name.ends_with(".snap") && !snap_names.contains(&name)) {
+        fs.remove_file(&path)?;
+      }
+    }
+    for path in fs.read_dir(&blobs_dir)? {
+      let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+      if file_name.is_some_and(|name| !blob_names.contains(&name)) {
+        fs.remove_file(&path)?;
+      }
+    }
+  }
+
+  Ok(drifts)
+}
+
+fn describe_drift(
+  name: &str,
+  old_snap: &str,
+  files: &BTreeMap<String, (String, Vec<u8>)>,
+  blobs_dir: &Path,
+  fs: &dyn FileSystem,
+) -> Result<String> {
+  let old_files = old_snap
+    .lines()
+    .filter_map(|line| line.rsplit_once(' '))
+    .collect::<BTreeMap<_, _>>();
+
+  let mut report = format!("{}:\n", name);
+  if old_snap.is_empty() {
+    report.push_str("  missing snapshot\n");
+  }
+
+  for path in old_files.keys() {
+    if !files.contains_key(*path) {
+      report.push_str(&format!("  - {}\n", path));
+    }
+  }
+
+  for (path, (hash, contents)) in files {
+    match old_files.get(path.as_str()) {
+      None if !old_snap.is_empty() => report.push_str(&format!("  + {}\n", path)),
+      Some(old_hash) if old_hash != hash => {
+        report.push_str(&format!("  ~ {}\n", path));
+        let old_contents = fs.read(&blobs_dir.join(old_hash))?;
+        match (old_contents, std::str::from_utf8(contents)) {
+          (Some(old_contents), Ok(new_contents)) => {
+            let old_contents = String::from_utf8_lossy(&old_contents);
+            let diff = TextDiff::from_lines(old_contents.as_ref(), new_contents);
+            let diff = diff.unified_diff().context_radius(2).to_string();
+            for line in diff.lines() {
+              report.push_str(&format!("    {}\n", line));
+            }
+          }
+          _ => report.push_str("    binary or missing blob changed\n"),
+        }
+      }
+      _ => {}
+    }
+  }
+
+  Ok(report)
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+fn hash(contents: &[u8]) -> String {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in contents {
+    hash ^= *byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  format!("{:016x}", hash)
+}
+
+#[test]
+fn test_combination_name() {
+  assert_eq!(combination_name(&ScaffoldOptions::default()), "default");
+  let options = ScaffoldOptions {
+    needs_type_script: true,
+    needs_router: true,
+    needs_e2e_testing: E2eTesting::Cypress,
+    ..Default::default()
+  };
+  assert_eq!(combination_name(&options), "ts-router-cypress");
+  let options = ScaffoldOptions {
+    needs_eslint: true,
+    needs_prettier: true,
+    eslint_style: StyleGuide::Standard,
+    ..Default::default()
+  };
+  assert_eq!(combination_name(&options), "eslint-standard-prettier");
+
+  let combinations = all_combinations();
+  assert_eq!(combinations.len(), 32 * 7 * 3);
+  assert!(combinations
+    .iter()
+    .all(|options| options.needs_eslint || !options.needs_prettier));
+}
+
+#[test]
+fn test_templates_match_snapshots() {
+  use crate::vfs::DiskFs;
+
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+  let drifts = verify_templates(&dir, false, &mut DiskFs).unwrap();
+  assert!(
+    drifts.is_empty(),
+    "templates drifted from the snapshots, run `create-vue-rust verify-templates --update` \
+     if this is intended:\n{}",
+    drifts.join("\n")
+  );
+}
+
+#[test]
+fn test_verify_templates_reports_drift() {
+  let dir = Path::new("/snapshots");
+  let mut fs = MemoryFs::new();
+  assert!(verify_templates(dir, true, &mut fs).unwrap().is_empty());
+  assert!(verify_templates(dir, false, &mut fs).unwrap().is_empty());
+
+  let snap_path = dir.join("default.snap");
+  let snap = fs.read_to_string(&snap_path).unwrap().unwrap();
+  let pkg_line = snap.lines().find(|line| line.starts_with("package.json ")).unwrap();
+  let pkg_hash = pkg_line.rsplit_once(' ').unwrap().1;
+  let pkg = fs.read_to_string(&dir.join("blobs").join(pkg_hash)).unwrap().unwrap();
+  let old_pkg = pkg.replace("vite build", "vite bundle");
+  fs.write(&dir.join("blobs/0"), old_pkg.as_bytes()).unwrap();
+  fs.write(&snap_path, snap.replace(pkg_line, "package.json 0").as_bytes()).unwrap();
+
+  let drifts = verify_templates(dir, false, &mut fs).unwrap();
+  assert_eq!(drifts.len(), 1);
+  assert!(drifts[0].starts_with("default:\n  ~ package.json\n"));
+  assert!(drifts[0].contains(r#"-    "build": "vite bundle","#));
+  assert!(drifts[0].contains(r#"+    "build": "vite build","#));
+}
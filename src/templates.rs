@@ -0,0 +1,36 @@
+use include_dir::{include_dir, Dir};
+
+use crate::error::{Error, Result};
+
+static TEMPLATE_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/src/template");
+
+include!(concat!(env!("OUT_DIR"), "/layers.rs"));
+
+/// The directory of a layer in the embedded template tree.
+pub fn get_template(layer: Layer) -> &'static Dir<'static> {
+  TEMPLATE_DIR
+    .get_dir(layer.path())
+    .expect("build.rs only generates layers that exist")
+}
+
+/// Looks up a file such as the manifest in the root of the embedded template tree.
+pub fn get_template_file(name: &str) -> Result<&'static [u8]> {
+  TEMPLATE_DIR
+    .get_file(name)
+    .map(|file| file.contents())
+    .ok_or_else(|| Error::TemplateMissing(name.to_string()))
+}
+
+#[test]
+fn test_get_template() {
+  let base = get_template(Layer::Base);
+  assert!(base.get_file("base/public/favicon.ico").is_some());
+  assert!(base.get_file("base/_gitignore").is_some());
+  assert_eq!(Layer::ConfigCypressCt.path(), "config/cypress-ct");
+  assert_eq!(Layer::from_path("config/jsx"), Some(Layer::ConfigJsx));
+  assert_eq!(Layer::from_path("config, jsx"), None);
+  for layer in Layer::ALL {
+    assert_eq!(Layer::from_path(layer.path()), Some(*layer));
+    assert!(!get_template(*layer).entries().is_empty());
+  }
+}
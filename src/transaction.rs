@@ -0,0 +1,318 @@
+use std::{
+  io,
+  panic::{self, AssertUnwindSafe},
+  path::{Component, Path, PathBuf},
+  sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+  error::{Error, Result},
+  vfs::FileSystem,
+};
+
+/// Generates a project into a staging directory next to `target`, then swaps it into place.
+///
+/// `write` receives the staging directory and a file system that fails with
+/// [`Error::Cancelled`] once `cancelled` is set, e.g. from a Ctrl-C handler. The existing
+/// contents of `target` are replaced on success, except `.git`, which is kept. On failure the
+/// staging directory is removed and `target` is left exactly as it was, even if `write` panics.
+pub fn stage<F>(target: &Path, fs: &mut dyn FileSystem, cancelled: &AtomicBool, write: F) -> Result<()>
+where
+  F: FnOnce(&Path, &mut dyn FileSystem) -> Result<()>,
+{
+  let mut transaction = Transaction::begin(target, fs)?;
+
+  let result = panic::catch_unwind(AssertUnwindSafe(|| {
+    write(
+      &transaction.staging,
+      &mut CancellableFs {
+        inner: fs,
+        cancelled,
+      },
+    )
+    .and_then(|_| {
+      if cancelled.load(Ordering::SeqCst) {
+        Err(Error::Cancelled)
+      } else {
+        transaction.commit(fs)
+      }
+    })
+  }));
+  let result = match result {
+    Ok(result) => result,
+    Err(payload) => {
+      let _ = transaction.rollback(fs);
+      panic::resume_unwind(payload);
+    }
+  };
+
+  if result.is_err() {
+    transaction.rollback(fs)?;
+  }
+  result
+}
+
+struct Transaction {
+  target: PathBuf,
+  staging: PathBuf,
+  backup: PathBuf,
+  /// Whether `target` was created by the commit, rather than existing before.
+  created: bool,
+  /// The entries of `target` moved into `backup`, then those of `staging` moved into `target`,
+  /// so a failed commit can be undone step by step.
+  moved_out: Vec<PathBuf>,
+  moved_in: Vec<PathBuf>,
+}
+
+impl Transaction {
+  fn begin(target: &Path, fs: &mut dyn FileSystem) -> Result<Self> {
+    // a relative target like `.` has no name and parent to put the staging directory next to
+    let mut absolute = PathBuf::new();
+    for component in std::path::absolute(target)?.components() {
+      match component {
+        Component::CurDir => {}
+        Component::ParentDir => {
+          absolute.pop();
+        }
+        component => absolute.push(component),
+      }
+    }
+    let target = absolute.as_path();
+    let name = target
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let parent = target.parent().unwrap_or(target);
+    let transaction = Self {
+      target: target.to_path_buf(),
+      staging: parent.join(format!(".{}.staging", name)),
+      backup: parent.join(format!(".{}.backup", name)),
+      created: false,
+      moved_out: vec![],
+      moved_in: vec![],
+    };
+
+    // a backup left over from an interrupted run still holds the user's files
+    if exists(fs, &transaction.backup) {
+      return Err(Error::Io(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!(
+          "{:?} was left over from an interrupted run, restore or remove it first",
+          transaction.backup
+        ),
+      )));
+    }
+    if exists(fs, &transaction.staging) {
+      fs.remove_dir_all(&transaction.staging)?;
+    }
+    fs.create_dir_all(&transaction.staging)?;
+
+    Ok(transaction)
+  }
+
+  /// Swaps the entries rather than `target` itself, so scaffolding into the current directory
+  /// leaves the shell in a directory that still exists.
+  fn commit(&mut self, fs: &mut dyn FileSystem) -> Result<()> {
+    fs.create_dir_all(&self.backup)?;
+    if exists(fs, &self.target) {
+      for path in fs.read_dir(&self.target)? {
+        let name = entry_name(&path);
+        if name != Path::new(".git") {
+          fs.rename(&path, &self.backup.join(&name))?;
+          self.moved_out.push(name);
+        }
+      }
+    } else {
+      fs.create_dir_all(&self.target)?;
+      self.created = true;
+    }
+    for path in fs.read_dir(&self.staging)? {
+      let name = entry_name(&path);
+      fs.rename(&path, &self.target.join(&name))?;
+      self.moved_in.push(name);
+    }
+    fs.remove_dir_all(&self.staging)?;
+    fs.remove_dir_all(&self.backup)?;
+    Ok(())
+  }
+
+  /// Undoes whatever part of [`Transaction::commit`] already happened.
+  fn rollback(&self, fs: &mut dyn FileSystem) -> Result<()> {
+    for name in self.moved_in.iter().rev() {
+      fs.rename(&self.target.join(name), &self.staging.join(name))?;
+    }
+    for name in self.moved_out.iter().rev() {
+      fs.rename(&self.backup.join(name), &self.target.join(name))?;
+    }
+    if self.created {
+      fs.remove_dir_all(&self.target)?;
+    }
+    for dir in [&self.staging, &self.backup] {
+      if exists(fs, dir) {
+        fs.remove_dir_all(dir)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn entry_name(path: &Path) -> PathBuf {
+  PathBuf::from(path.file_name().unwrap_or_default())
+}
+
+fn exists(fs: &dyn FileSystem, path: &Path) -> bool {
+  fs.is_dir(path) || fs.is_file(path)
+}
+
+/// Fails every change once `cancelled` is set, so generation stops at the next file.
+struct CancellableFs<'a> {
+  inner: &'a mut dyn FileSystem,
+  cancelled: &'a AtomicBool,
+}
+
+impl CancellableFs<'_> {
+  fn check(&self) -> Result<()> {
+    if self.cancelled.load(Ordering::SeqCst) {
+      Err(Error::Cancelled)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+impl FileSystem for CancellableFs<'_> {
+  fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+    self.inner.read(path)
+  }
+
+  fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+    self.check()?;
+    self.inner.write(path, contents)
+  }
+
+  fn remove_file(&mut self, path: &Path) -> Result<()> {
+    self.check()?;
+    self.inner.remove_file(path)
+  }
+
+  fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+    self.check()?;
+    self.inner.rename(from, to)
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    self.inner.is_file(path)
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    self.inner.is_dir(path)
+  }
+
+  fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+    self.inner.read_dir(path)
+  }
+
+  fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+    self.check()?;
+    self.inner.create_dir_all(path)
+  }
+
+  fn remove_dir_all(&mut self, path: &Path) -> Result<()> {
+    self.check()?;
+    self.inner.remove_dir_all(path)
+  }
+}
+
+#[test]
+fn test_stage() {
+  use crate::vfs::MemoryFs;
+
+  let target = Path::new("/work/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(&target.join("old.txt"), b"old").unwrap();
+  fs.write(&target.join(".git/HEAD"), b"ref").unwrap();
+
+  // a failure halfway leaves the target untouched
+  let not_cancelled = AtomicBool::new(false);
+  let result = stage(target, &mut fs, &not_cancelled, |staging, fs| {
+    fs.write(&staging.join("new.txt"), b"new")?;
+    Err(Error::Merge("broken".to_string()))
+  });
+  assert!(matches!(result, Err(Error::Merge(_))));
+  assert_eq!(
+    fs.files(Path::new("/work")).unwrap(),
+    vec![target.join(".git/HEAD"), target.join("old.txt")]
+  );
+
+  // so does a cancellation
+  let cancelled = AtomicBool::new(false);
+  let result = stage(target, &mut fs, &cancelled, |staging, fs| {
+    fs.write(&staging.join("new.txt"), b"new")?;
+    cancelled.store(true, Ordering::SeqCst);
+    fs.write(&staging.join("other.txt"), b"new")
+  });
+  assert!(matches!(result, Err(Error::Cancelled)));
+  assert_eq!(
+    fs.files(Path::new("/work")).unwrap(),
+    vec![target.join(".git/HEAD"), target.join("old.txt")]
+  );
+
+  // success replaces everything but .git
+  stage(target, &mut fs, &not_cancelled, |staging, fs| {
+    fs.write(&staging.join("new.txt"), b"new")
+  })
+  .unwrap();
+  assert_eq!(
+    fs.files(Path::new("/work")).unwrap(),
+    vec![target.join(".git/HEAD"), target.join("new.txt")]
+  );
+}
+
+#[test]
+fn test_stage_panic() {
+  use crate::vfs::MemoryFs;
+
+  let target = Path::new("/work/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(&target.join("old.txt"), b"old").unwrap();
+
+  let result = panic::catch_unwind(AssertUnwindSafe(|| {
+    stage(target, &mut fs, &AtomicBool::new(false), |staging, fs| {
+      fs.write(&staging.join("new.txt"), b"new")?;
+      panic!("broken template");
+    })
+  }));
+  assert!(result.is_err());
+  assert_eq!(
+    fs.files(Path::new("/work")).unwrap(),
+    vec![target.join("old.txt")]
+  );
+  assert!(!fs.is_dir(Path::new("/work/.vue-project.staging")));
+}
+
+#[test]
+fn test_rollback_after_swap() {
+  use crate::vfs::MemoryFs;
+
+  let target = Path::new("/work/vue-project");
+  let mut fs = MemoryFs::new();
+  fs.write(&target.join("old.txt"), b"old").unwrap();
+  fs.write(&target.join(".git/HEAD"), b"ref").unwrap();
+
+  let mut transaction = Transaction::begin(target, &mut fs).unwrap();
+  fs.write(&transaction.staging.join("new.txt"), b"new").unwrap();
+  // interrupted right after the staged files were moved into place
+  fs.rename(&target.join("old.txt"), &transaction.backup.join("old.txt"))
+    .unwrap();
+  transaction.moved_out.push(PathBuf::from("old.txt"));
+  fs.rename(&transaction.staging.join("new.txt"), &target.join("new.txt"))
+    .unwrap();
+  transaction.moved_in.push(PathBuf::from("new.txt"));
+
+  transaction.rollback(&mut fs).unwrap();
+  assert_eq!(
+    fs.files(Path::new("/work")).unwrap(),
+    vec![target.join(".git/HEAD"), target.join("old.txt")]
+  );
+  assert!(!fs.is_dir(&transaction.staging) && !fs.is_dir(&transaction.backup));
+}
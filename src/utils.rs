@@ -1,121 +1,92 @@
-use anyhow::{anyhow, Result};
 use regex::Regex;
-use std::{env, fs, path::PathBuf};
+use std::{
+  env,
+  path::{Path, PathBuf},
+};
 
-pub fn can_skip_emptying(target_dir: &String) -> Result<bool> {
-  let root_path = env::current_dir().unwrap();
-  let target_dir: PathBuf = target_dir.into();
-  let target_path = root_path.join(&target_dir);
-  if !target_path.is_dir() & !target_path.is_file() {
+use crate::{
+  error::{Error, Result},
+  vfs::FileSystem,
+};
+
+pub fn can_skip_emptying(fs: &dyn FileSystem, target_path: &Path) -> Result<bool> {
+  if !fs.is_dir(target_path) & !fs.is_file(target_path) {
     return Ok(true);
-  } else if target_path.is_file() {
-    return Err(anyhow!("not a directory, scandir {:?}", target_dir));
-  }
-  let dir = fs::read_dir(target_path).unwrap();
-  let mut count = 0;
-  for x in dir {
-    if let Ok(path) = x {
-      if path.file_name().to_str().unwrap() != ".git" {
-        count += 1;
-      }
-    }
+  } else if fs.is_file(target_path) {
+    return Err(Error::TargetIsFile(target_path.to_path_buf()));
   }
-  return Ok(count == 0);
+  let count = fs
+    .read_dir(target_path)?
+    .iter()
+    .filter(|path| path.file_name().is_some_and(|name| name != ".git"))
+    .count();
+  Ok(count == 0)
 }
 
-pub fn is_valid_package_name(package_name: &String) -> Result<bool> {
-  let reg = Regex::new(r"^(?:@[a-z0-9-*~][a-z0-9-*._~]*/)?[a-z0-9-~][a-z0-9-._~]*$")?;
-  Ok(reg.is_match(package_name))
+pub fn is_valid_package_name(package_name: &str) -> bool {
+  let reg = Regex::new(r"^(?:@[a-z0-9-*~][a-z0-9-*._~]*/)?[a-z0-9-~][a-z0-9-._~]*$").unwrap();
+  reg.is_match(package_name)
 }
 
-pub fn to_valid_package_name(package_name: &String) -> Result<String> {
-  let result = package_name.clone();
-
-  let result = Regex::new(r"\s+")?
-    .replace_all(&result.trim(), "_")
+pub fn to_valid_package_name(package_name: &str) -> String {
+  let result = Regex::new(r"\s+")
+    .unwrap()
+    .replace_all(package_name.trim(), "_")
     .to_string();
 
-  let result = Regex::new(r"^[._]")?.replace(&result, "").to_string();
-  let result = Regex::new(r"[^a-z0-9-~]+")?
+  let result = Regex::new(r"^[._]").unwrap().replace(&result, "").to_string();
+  Regex::new(r"[^a-z0-9-~]+")
+    .unwrap()
     .replace_all(&result, "-")
-    .to_string();
-  Ok(result)
+    .to_string()
 }
 
-
-pub fn get_path_from_cwd(path: &String) -> Result<PathBuf> {
-  let root_path = env::current_dir().unwrap();
-  let path: PathBuf = path.into();
-  let path = root_path.join(&path);
-  Ok(path)
+pub fn get_path_from_cwd(path: &str) -> Result<PathBuf> {
+  let root_path = env::current_dir()?;
+  // `components` drops the `.` of `create-vue-rust .`, so the path ends in the directory's name
+  Ok(root_path.join(path).components().collect())
 }
 
-pub fn empty_dir(target_dir: &String) -> Result<()> {
-  
-  let target_path = get_path_from_cwd(target_dir)?;
-  if !target_path.is_dir() & !target_path.is_file() {
-    return Ok(());
-  } else if target_path.is_file() {
-    return Err(anyhow!("not a directory, scandir {:?}", target_dir));
-  }
-  let dir = fs::read_dir(target_path).unwrap();
-  for x in dir {
-    if let Ok(path) = x {
-      if path.file_name().to_str().unwrap() != ".git" {
-        let path = path.path();
-        if path.is_dir() {
-          fs::remove_dir_all(path)?;
-        } else {
-          fs::remove_file(path)?;
-        }
-      }
-    }
+pub fn mkdir(fs: &mut dyn FileSystem, target_path: &Path) -> Result<()> {
+  if !fs.is_dir(target_path) & !fs.is_file(target_path) {
+    fs.create_dir_all(target_path)?;
   }
   Ok(())
 }
 
-
-pub fn mkdir(target_dir: &String) -> Result<()> {
-  let target_path = get_path_from_cwd(&target_dir)?;
-  if !target_path.is_dir() & !target_path.is_file() {
-    fs::create_dir_all(target_path)?;
-  }
-  Ok(())
-}
-
-
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_is_valid_pageage_name() {
   assert_eq!(
-    is_valid_package_name(&String::from("_dsdsd")).unwrap(),
+    is_valid_package_name(&String::from("_dsdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from(".dsdsd")).unwrap(),
+    is_valid_package_name(&String::from(".dsdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from(r"d\sdsd")).unwrap(),
+    is_valid_package_name(&String::from(r"d\sdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from("d!sdsd")).unwrap(),
+    is_valid_package_name(&String::from("d!sdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from("d`sdsd")).unwrap(),
+    is_valid_package_name(&String::from("d`sdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from("d(sdsd")).unwrap(),
+    is_valid_package_name(&String::from("d(sdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from("d)sdsd")).unwrap(),
+    is_valid_package_name(&String::from("d)sdsd")),
     false
   );
   assert_eq!(
-    is_valid_package_name(&String::from("ds'dsd")).unwrap(),
+    is_valid_package_name(&String::from("ds'dsd")),
     false
   );
 }
@@ -123,24 +94,33 @@ fn test_is_valid_pageage_name() {
 #[test]
 fn test_to_valid_package_name() {
   assert_eq!(
-    to_valid_package_name(&String::from(" dsdsd")).unwrap(),
+    to_valid_package_name(&String::from(" dsdsd")),
     "dsdsd".to_string()
   );
   assert_eq!(
-    to_valid_package_name(&String::from("d sdsd")).unwrap(),
+    to_valid_package_name(&String::from("d sdsd")),
     "d-sdsd".to_string()
   );
 
   assert_eq!(
-    to_valid_package_name(&String::from("d%%%sdsd")).unwrap(),
+    to_valid_package_name(&String::from("d%%%sdsd")),
     "d-sdsd".to_string()
   );
 }
 
 #[test]
-fn test_emoty_dir(){
-  match empty_dir(&String::from("lang")){
-    Ok(_) => {},
-    _ => {}
-  };
+fn test_emoty_dir() {
+  use crate::vfs::MemoryFs;
+
+  let root = Path::new("/project");
+  let mut fs = MemoryFs::new();
+  assert!(can_skip_emptying(&fs, root).unwrap());
+
+  fs.write(&root.join(".git/HEAD"), b"").unwrap();
+  assert!(can_skip_emptying(&fs, root).unwrap());
+
+  fs.write(&root.join("README.md"), b"").unwrap();
+  fs.write(&root.join("src/main.js"), b"").unwrap();
+  assert!(!can_skip_emptying(&fs, root).unwrap());
+  assert!(can_skip_emptying(&fs, &root.join("README.md")).is_err());
 }
\ No newline at end of file
@@ -0,0 +1,292 @@
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::error::Result;
+
+/// The file operations scaffolding needs. Paths are always full paths, never relative to the
+/// current directory.
+pub trait FileSystem {
+  fn read(&self, path: &Path) -> Result<Option<Vec<u8>>>;
+  /// Writes a file, creating its parent directories as needed.
+  fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()>;
+  fn remove_file(&mut self, path: &Path) -> Result<()>;
+  fn rename(&mut self, from: &Path, to: &Path) -> Result<()>;
+  fn is_file(&self, path: &Path) -> bool;
+  fn is_dir(&self, path: &Path) -> bool;
+  /// The direct children of a directory.
+  fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>>;
+  fn create_dir_all(&mut self, path: &Path) -> Result<()>;
+  fn remove_dir_all(&mut self, path: &Path) -> Result<()>;
+
+  /// The target of a symbolic link, or `None` if `path` is not one. The link is not followed.
+  fn read_link(&self, _path: &Path) -> Result<Option<PathBuf>> {
+    Ok(None)
+  }
+
+  /// The permission bits of a file or directory, e.g. to keep scripts executable.
+  fn mode(&self, path: &Path) -> Result<u32> {
+    Ok(if self.is_dir(path) { 0o755 } else { 0o644 })
+  }
+
+  fn read_to_string(&self, path: &Path) -> Result<Option<String>> {
+    Ok(
+      self
+        .read(path)?
+        .map(|contents| String::from_utf8_lossy(&contents).into_owned()),
+    )
+  }
+
+  /// Every file below `root`, recursively.
+  fn files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    if self.is_dir(root) {
+      for path in self.read_dir(root)? {
+        if self.is_dir(&path) {
+          files.extend(self.files(&path)?);
+        } else {
+          files.push(path);
+        }
+      }
+    }
+    files.sort();
+    Ok(files)
+  }
+}
+
+/// The real disk.
+#[derive(Debug, Default)]
+pub struct DiskFs;
+
+impl FileSystem for DiskFs {
+  fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+    if path.is_file() {
+      Ok(Some(fs::read(path)?))
+    } else {
+      Ok(None)
+    }
+  }
+
+  fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+  }
+
+  fn remove_file(&mut self, path: &Path) -> Result<()> {
+    fs::remove_file(path)?;
+    Ok(())
+  }
+
+  fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+    fs::rename(from, to)?;
+    Ok(())
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    path.is_file()
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    path.is_dir()
+  }
+
+  fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(path)? {
+      paths.push(entry?.path());
+    }
+    paths.sort();
+    Ok(paths)
+  }
+
+  fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+    fs::create_dir_all(path)?;
+    Ok(())
+  }
+
+  fn remove_dir_all(&mut self, path: &Path) -> Result<()> {
+    fs::remove_dir_all(path)?;
+    Ok(())
+  }
+
+  fn read_link(&self, path: &Path) -> Result<Option<PathBuf>> {
+    match fs::symlink_metadata(path) {
+      Ok(metadata) if metadata.file_type().is_symlink() => Ok(Some(fs::read_link(path)?)),
+      _ => Ok(None),
+    }
+  }
+
+  #[cfg(unix)]
+  fn mode(&self, path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    Ok(fs::symlink_metadata(path)?.permissions().mode() & 0o7777)
+  }
+}
+
+/// Keeps every file in memory, e.g. for `--dry-run`, tests, or to zip a project up.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+  files: BTreeMap<PathBuf, Vec<u8>>,
+  dirs: BTreeSet<PathBuf>,
+}
+
+impl MemoryFs {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// All files with their contents, ordered by path.
+  pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Vec<u8>)> {
+    self.files.iter()
+  }
+
+  /// Renders the files below `root` as an indented tree.
+  pub fn tree(&self, root: &Path) -> String {
+    let mut result = String::new();
+    let mut printed_dirs: Vec<PathBuf> = vec![];
+
+    for path in self.files.keys() {
+      let relative = match path.strip_prefix(root) {
+        Ok(relative) => relative,
+        Err(_) => continue,
+      };
+
+      let mut current = PathBuf::new();
+      let components = relative.components().collect::<Vec<_>>();
+      for (depth, component) in components.iter().enumerate() {
+        current.push(component);
+        let is_file = depth == components.len() - 1;
+        if !is_file && printed_dirs.contains(&current) {
+          continue;
+        }
+        let name = component.as_os_str().to_string_lossy();
+        let suffix = if is_file { "" } else { "/" };
+        result.push_str(&format!("{}{}{}\n", "  ".repeat(depth), name, suffix));
+        if !is_file {
+          printed_dirs.push(current.clone());
+        }
+      }
+    }
+
+    result
+  }
+}
+
+impl FileSystem for MemoryFs {
+  fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+    Ok(self.files.get(path).cloned())
+  }
+
+  fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+      self.create_dir_all(parent)?;
+    }
+    self.files.insert(path.to_path_buf(), contents.to_vec());
+    Ok(())
+  }
+
+  fn remove_file(&mut self, path: &Path) -> Result<()> {
+    self.files.remove(path);
+    Ok(())
+  }
+
+  fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+    if let Some(contents) = self.files.remove(from) {
+      return self.write(to, &contents);
+    }
+    if !self.dirs.contains(from) {
+      return Ok(());
+    }
+
+    let moved_files = self
+      .files
+      .keys()
+      .filter(|file| file.starts_with(from))
+      .cloned()
+      .collect::<Vec<_>>();
+    let moved_dirs = self
+      .dirs
+      .iter()
+      .filter(|dir| dir.starts_with(from))
+      .cloned()
+      .collect::<Vec<_>>();
+    for file in moved_files {
+      let contents = self.files.remove(&file).unwrap_or_default();
+      self.write(&to.join(file.strip_prefix(from).unwrap_or(&file)), &contents)?;
+    }
+    for dir in moved_dirs {
+      self.dirs.remove(&dir);
+      self.create_dir_all(&to.join(dir.strip_prefix(from).unwrap_or(&dir)))?;
+    }
+    Ok(())
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    self.files.contains_key(path)
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    self.dirs.contains(path)
+  }
+
+  fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>> {
+    let children = self
+      .files
+      .keys()
+      .chain(self.dirs.iter())
+      .filter(|child| child.parent() == Some(path))
+      .cloned()
+      .collect::<BTreeSet<_>>();
+    Ok(children.into_iter().collect())
+  }
+
+  fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+    for ancestor in path.ancestors() {
+      if ancestor.as_os_str().is_empty() {
+        break;
+      }
+      self.dirs.insert(ancestor.to_path_buf());
+    }
+    Ok(())
+  }
+
+  fn remove_dir_all(&mut self, path: &Path) -> Result<()> {
+    self.files.retain(|file, _| !file.starts_with(path));
+    self.dirs.retain(|dir| !dir.starts_with(path));
+    Ok(())
+  }
+}
+
+#[test]
+fn test_memory_fs() {
+  let root = Path::new("/project");
+  let mut fs = MemoryFs::new();
+  fs.write(&root.join("package.json"), b"{}").unwrap();
+  fs.write(&root.join("src/main.js"), b"").unwrap();
+  fs.write(&root.join("src/components/App.vue"), b"").unwrap();
+  fs.rename(&root.join("src/main.js"), &root.join("src/main.ts")).unwrap();
+
+  assert!(fs.is_dir(&root.join("src/components")));
+  assert_eq!(
+    fs.read_dir(&root.join("src")).unwrap(),
+    vec![root.join("src/components"), root.join("src/main.ts")]
+  );
+  assert_eq!(
+    fs.tree(root),
+    "package.json\nsrc/\n  components/\n    App.vue\n  main.ts\n"
+  );
+
+  fs.rename(&root.join("src"), &root.join("lib")).unwrap();
+  assert!(!fs.is_dir(&root.join("src")));
+  assert!(fs.is_file(&root.join("lib/components/App.vue")));
+
+  fs.remove_dir_all(&root.join("lib")).unwrap();
+  assert!(!fs.is_dir(&root.join("lib")));
+  assert_eq!(fs.files(root).unwrap(), vec![root.join("package.json")]);
+}